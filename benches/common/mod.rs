@@ -0,0 +1,51 @@
+use std::{fmt::Debug, hint::black_box};
+
+use aoc::Solution;
+use criterion::Criterion;
+
+/// Time both parts of `S`, part 1 on `input_1` and part 2 on `input_2`. The inputs are split into
+/// lines beforehand, so that only the solving (including parsing the lines) is timed.
+///
+/// Both parts are solved once before timing them, so that a generated input the day rejects
+/// fails the benchmark instead of timing how fast the error comes back.
+pub fn bench_day<S: Solution>(c: &mut Criterion, day: &str, input_1: &str, input_2: &str)
+where
+    S::Error: Debug,
+{
+    let input_1 = aoc::to_lines(input_1);
+    let input_2 = aoc::to_lines(input_2);
+
+    if let Err(e) = S::part1(&input_1) {
+        panic!("{day} part 1 failed on the benchmark input: {e:?}");
+    }
+    if let Err(e) = S::part2(&input_2) {
+        panic!("{day} part 2 failed on the benchmark input: {e:?}");
+    }
+
+    c.bench_function(&format!("{day} part 1"), |b| {
+        b.iter(|| S::part1(black_box(&input_1)))
+    });
+    c.bench_function(&format!("{day} part 2"), |b| {
+        b.iter(|| S::part2(black_box(&input_2)))
+    });
+}
+
+/// A small xorshift generator for the benchmark inputs, so that every run times the same input
+/// without depending on a random number crate.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift never leaves 0.
+        Self(seed.max(1))
+    }
+
+    /// A value in `0..bound`, which must not be empty.
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % bound as u64) as usize
+    }
+}
@@ -0,0 +1,45 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day01.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day01;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// 1000 lines of letters and spelled out digits, each with at least one digit.
+fn generate_input(rng: &mut Rng) -> String {
+    let mut input = String::new();
+
+    for _ in 0..1000 {
+        let pieces = 5 + rng.below(20);
+        let digit_at = rng.below(pieces);
+
+        for i in 0..pieces {
+            match rng.below(4) {
+                _ if i == digit_at => input.push(char::from(b'1' + rng.below(9) as u8)),
+                0 => input.push_str(WORDS[rng.below(WORDS.len())]),
+                _ => input.push(char::from(b'a' + rng.below(26) as u8)),
+            }
+        }
+
+        input.push('\n');
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(1));
+
+    common::bench_day::<day01::Day01>(c, "day01", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
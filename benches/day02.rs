@@ -0,0 +1,45 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day02.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day02;
+
+use std::fmt::Write;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const COLOURS: [&str; 3] = ["red", "green", "blue"];
+
+/// 1000 games of up to 6 draws, each of one to three colours.
+fn generate_input(rng: &mut Rng) -> String {
+    let mut input = String::new();
+
+    for id in 1..=1000 {
+        let draws = (0..1 + rng.below(6))
+            .map(|_| {
+                let first = rng.below(COLOURS.len());
+
+                (0..1 + rng.below(COLOURS.len()))
+                    .map(|i| format!("{} {}", 1 + rng.below(20), COLOURS[(first + i) % 3]))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>();
+
+        writeln!(input, "Game {id}: {}", draws.join("; ")).unwrap();
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(2));
+
+    common::bench_day::<day02::Day02>(c, "day02", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day03.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day03;
+
+use std::fmt::Write;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const SIZE: usize = 140;
+const SYMBOLS: &[u8] = b"**#+$/@=%&-";
+
+/// A 140x140 schematic, about a fifth numbers and a tenth symbols.
+fn generate_input(rng: &mut Rng) -> String {
+    let mut input = String::new();
+
+    for _ in 0..SIZE {
+        let mut row = String::new();
+
+        while row.len() < SIZE {
+            match rng.below(10) {
+                0 => row.push(char::from(SYMBOLS[rng.below(SYMBOLS.len())])),
+                1 | 2 => write!(row, "{}.", 1 + rng.below(999)).unwrap(),
+                _ => row.push('.'),
+            }
+        }
+
+        row.truncate(SIZE);
+        input.push_str(&row);
+        input.push('\n');
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(3));
+
+    common::bench_day::<day03::Day03>(c, "day03", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day04.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day04;
+
+use std::fmt::Write;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// 1000 cards of 10 winning numbers and 25 numbers we have, between 1 and 500. That makes about
+/// one match every two cards, so that the number of copies stays small.
+fn generate_input(rng: &mut Rng) -> String {
+    let mut input = String::new();
+    let mut numbers = |count: usize| {
+        (0..count)
+            .map(|_| format!("{:>3}", 1 + rng.below(500)))
+            .collect::<Vec<_>>()
+    };
+
+    for id in 1..=1000 {
+        let left = numbers(10).join(" ");
+        let right = numbers(25).join(" ");
+
+        writeln!(input, "Card {id:>4}: {left} | {right}").unwrap();
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(4));
+
+    common::bench_day::<day04::Day04>(c, "day04", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day05.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day05;
+
+use std::{fmt::Write, hint::black_box};
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+
+/// 10 seed ranges of up to 10000 seeds, and 40 maps per layer, with values below 2^32. The seed
+/// ranges are kept short so that converting every seed (with the `parallel` feature) stays quick.
+fn generate_input(rng: &mut Rng) -> String {
+    let mut input = String::from("seeds:");
+
+    for _ in 0..10 {
+        write!(input, " {} {}", rng.below(1 << 32), 1 + rng.below(10_000)).unwrap();
+    }
+    input.push('\n');
+
+    for (from, to) in CATEGORIES.iter().zip(&CATEGORIES[1..]) {
+        write!(input, "\n{from}-to-{to} map:\n").unwrap();
+
+        // Maps of a layer can't overlap, so lay them out one after the other.
+        let mut source = 0;
+
+        for _ in 0..40 {
+            source += rng.below(1 << 24);
+            let length = 1 + rng.below(1 << 26);
+
+            writeln!(input, "{} {source} {length}", rng.below(1 << 32)).unwrap();
+            source += length;
+        }
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(5));
+
+    common::bench_day::<day05::Day05>(c, "day05", &input, &input);
+}
+
+/// Converting many seeds one at a time, against one layer at a time with `convert_seeds_batched`.
+fn bench_seed_conversion(c: &mut Criterion) {
+    let mut rng = Rng::new(5);
+    let input = aoc::to_lines(&generate_input(&mut rng));
+    let almanac: day05::Almanac = input.as_slice().try_into().unwrap();
+    let seeds: Vec<usize> = (0..100_000).map(|_| rng.below(1 << 32)).collect();
+
+    let mut group = c.benchmark_group("day05 seed conversion");
+    group.bench_function("per seed", |b| {
+        b.iter(|| {
+            black_box(&seeds)
+                .iter()
+                .map(|&seed| almanac.convert_seed(seed))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("batched", |b| {
+        b.iter(|| almanac.convert_seeds_batched(black_box(&seeds)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench, bench_seed_conversion);
+criterion_main!(benches);
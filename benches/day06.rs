@@ -0,0 +1,36 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day06.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day06;
+
+use std::fmt::Write;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// Four races, like the puzzle input: more races would make the merged race of part 2 overflow.
+fn generate_input(rng: &mut Rng) -> String {
+    let (mut times, mut distances) = (String::from("Time:"), String::from("Distance:"));
+
+    for _ in 0..4 {
+        let time = 40 + rng.below(60);
+        let hold = 1 + rng.below(time / 3);
+
+        write!(times, " {time:>4}").unwrap();
+        write!(distances, " {:>4}", hold * (time - hold)).unwrap();
+    }
+
+    format!("{times}\n{distances}\n")
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(6));
+
+    common::bench_day::<day06::Day06>(c, "day06", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
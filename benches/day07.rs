@@ -0,0 +1,38 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day07.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day07;
+
+use std::fmt::Write;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const CARDS: &[u8] = b"23456789TJQKA";
+
+/// 1000 hands of random cards.
+fn generate_input(rng: &mut Rng) -> String {
+    let mut input = String::new();
+
+    for _ in 0..1000 {
+        let hand: String = (0..5)
+            .map(|_| char::from(CARDS[rng.below(CARDS.len())]))
+            .collect();
+
+        writeln!(input, "{hand} {}", 1 + rng.below(1000)).unwrap();
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(7));
+
+    common::bench_day::<day07::Day07>(c, "day07", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
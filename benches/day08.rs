@@ -0,0 +1,70 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day08.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day08;
+
+use std::fmt::Write;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const MOVES: usize = 50;
+const PERIODS: [usize; 6] = [7, 11, 13, 17, 19, 23];
+
+/// A node name from `n`, ending with neither `A` nor `Z`.
+fn node_name(n: usize) -> String {
+    [n / 24 / 24, n / 24, n]
+        .iter()
+        .map(|&digit| char::from(b'B' + (digit % 24) as u8))
+        .collect()
+}
+
+/// Like the puzzle input: the walk from every `A` node reaches a `Z` node after a multiple of the
+/// number of moves, then loops back to the node after the `A` one. The move not taken from a node
+/// leads to a random node of the same walk. The first walk goes from `AAA` to `ZZZ`.
+fn generate_input(rng: &mut Rng) -> String {
+    let moves: String = (0..MOVES)
+        .map(|_| if rng.below(2) == 0 { 'L' } else { 'R' })
+        .collect();
+    let mut input = format!("{moves}\n\n");
+    let mut names = 0;
+
+    for (walk, period) in PERIODS.into_iter().enumerate() {
+        let length = period * MOVES;
+        let letter = char::from(b'A' + walk as u8);
+
+        let mut nodes = vec![format!("{letter}{letter}A")];
+        nodes.extend((names..names + length - 1).map(node_name));
+        nodes.push(format!("{letter}{letter}Z"));
+        names += length - 1;
+
+        if walk == 0 {
+            nodes[0] = "AAA".to_owned();
+            nodes[length] = "ZZZ".to_owned();
+        }
+
+        for (step, node) in nodes.iter().enumerate() {
+            let next = &nodes[if step == length { 1 } else { step + 1 }];
+            let other = &nodes[rng.below(nodes.len())];
+
+            match moves.as_bytes()[step % MOVES] {
+                b'L' => writeln!(input, "{node} = ({next}, {other})").unwrap(),
+                _ => writeln!(input, "{node} = ({other}, {next})").unwrap(),
+            }
+        }
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(8));
+
+    common::bench_day::<day08::Day08>(c, "day08", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
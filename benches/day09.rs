@@ -0,0 +1,47 @@
+mod common;
+
+// The day is compiled as a module of this benchmark. Its `main` allows going unused, but checking
+// the benchmarks also compiles the day's tests module without its tests.
+#[path = "../src/bin/day09.rs"]
+#[cfg_attr(test, allow(dead_code, unused_imports))]
+mod day09;
+
+use std::fmt::Write;
+
+use common::Rng;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// 200 sequences of 21 values of polynomials of degree up to 5.
+fn generate_input(rng: &mut Rng) -> String {
+    let mut input = String::new();
+
+    for _ in 0..200 {
+        let coefficients: Vec<i64> = (0..1 + rng.below(6))
+            .map(|_| rng.below(19) as i64 - 9)
+            .collect();
+
+        let values: Vec<String> = (0..21)
+            .map(|x: i64| {
+                let value: i64 = coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |value, coefficient| value * x + coefficient);
+
+                value.to_string()
+            })
+            .collect();
+
+        writeln!(input, "{}", values.join(" ")).unwrap();
+    }
+
+    input
+}
+
+fn bench(c: &mut Criterion) {
+    let input = generate_input(&mut Rng::new(9));
+
+    common::bench_day::<day09::Day09>(c, "day09", &input, &input);
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
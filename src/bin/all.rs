@@ -0,0 +1,78 @@
+use std::{fmt::Debug, time::Instant};
+
+use aoc::{read_lines, Solution};
+
+// Every day is compiled as a module of this binary, where their `main`s go unused (which they
+// allow).
+#[path = "day01.rs"]
+mod day01;
+#[path = "day02.rs"]
+mod day02;
+#[path = "day03.rs"]
+mod day03;
+#[path = "day04.rs"]
+mod day04;
+#[path = "day05.rs"]
+mod day05;
+#[path = "day06.rs"]
+mod day06;
+#[path = "day07.rs"]
+mod day07;
+#[path = "day08.rs"]
+mod day08;
+#[path = "day09.rs"]
+mod day09;
+
+fn main() {
+    println!(
+        "{:>3}  {:>4}  {:>20}  {:>12}",
+        "Day", "Part", "Answer", "Time"
+    );
+
+    run_day::<day01::Day01>(1);
+    run_day::<day02::Day02>(2);
+    run_day::<day03::Day03>(3);
+    run_day::<day04::Day04>(4);
+    run_day::<day05::Day05>(5);
+    run_day::<day06::Day06>(6);
+    run_day::<day07::Day07>(7);
+    run_day::<day08::Day08>(8);
+    run_day::<day09::Day09>(9);
+}
+
+/// Print a row with the answer to each part of `S`, and how long it took. Days without an input
+/// file are skipped with a warning.
+fn run_day<S: Solution>(day: usize)
+where
+    S::Error: Debug,
+{
+    let path = S::input_path();
+
+    if !path.exists() {
+        eprintln!("Skipping day {day}: {} is missing", path.display());
+
+        return;
+    }
+
+    let input = match read_lines(&path) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Skipping day {day}: {e}");
+
+            return;
+        }
+    };
+
+    let parts = [(1, S::part1 as fn(&_) -> _), (2, S::part2)];
+
+    for (part, solve) in parts {
+        let start = Instant::now();
+        let answer = match solve(&input) {
+            Ok(answer) => answer.to_string(),
+            Err(e) => format!("{e:?}"),
+        };
+        let elapsed = start.elapsed();
+
+        println!("{day:>3}  {part:>4}  {answer:>20}  {elapsed:>12.2?}");
+    }
+}
@@ -1,73 +1,135 @@
-use std::{io, num::ParseIntError};
+use std::{error, fmt, io};
 
-use aoc::read_lines;
+#[cfg(feature = "timings")]
+use std::time::Instant;
+
+#[cfg(feature = "timings")]
+use aoc::Solved;
+use aoc::{parse_int, Solution};
 use itertools::Itertools;
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
-    NoDigits,
+pub(crate) enum AocError {
+    Common(aoc::Error),
+    /// The line, which has no digits.
+    NoDigits(String),
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::NoDigits(line) => write!(f, "no digits in line {line:?}"),
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+impl From<aoc::Error> for AocError {
+    fn from(e: aoc::Error) -> Self {
+        Self::Common(e)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day01.txt";
+pub(crate) struct Day01;
 
-fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+impl Solution for Day01 {
+    const INPUT_FILE: &'static str = "day01.txt";
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    type Output = usize;
+    type Error = AocError;
 
-    Ok(())
+    fn part1(input: &[String]) -> Result<usize, AocError> {
+        part1(input)
+    }
+
+    fn part2(input: &[String]) -> Result<usize, AocError> {
+        part2(input)
+    }
+
+    /// Finding the digits of every line counts as parsing, for both parts.
+    #[cfg(feature = "timings")]
+    fn solve(input: &[String]) -> Result<Solved, AocError> {
+        let start = Instant::now();
+        let digits_1: Vec<_> = input.iter().map(get_first_and_last_digits).try_collect()?;
+        let digits_2: Vec<_> = input
+            .iter()
+            .map(get_first_and_last_digits_2)
+            .try_collect()?;
+        let parse_time = start.elapsed();
+
+        let start = Instant::now();
+        let part1 = sum_calibration_values(&digits_1)?;
+        let part2 = sum_calibration_values(&digits_2)?;
+        let solve_time = start.elapsed();
+
+        Ok(Solved {
+            part1: part1.to_string(),
+            part2: part2.to_string(),
+            parse_time,
+            solve_time,
+        })
+    }
+}
+
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
+fn main() -> Result<(), AocError> {
+    aoc::run::<Day01>()
 }
 
 fn part1(input: &[String]) -> Result<usize, AocError> {
-    let first_last_digits = input.iter().map(get_first_and_last_digits);
+    let first_last_digits: Vec<_> = input.iter().map(get_first_and_last_digits).try_collect()?;
 
+    sum_calibration_values(&first_last_digits)
+}
+
+fn sum_calibration_values(first_last_digits: &[(char, char)]) -> Result<usize, AocError> {
     let calibration_values: Vec<_> = first_last_digits
-        .map(|result| result.and_then(get_number_from_digits))
+        .iter()
+        .map(|&digits| get_number_from_digits(digits))
         .try_collect()?;
 
     Ok(calibration_values.iter().sum())
 }
 
+/// Only ASCII digits count: other numeric characters (e.g. `²` or `٣`) are ignored.
 fn get_first_and_last_digits<S: AsRef<str>>(line: S) -> Result<(char, char), AocError> {
-    let line_digits = line
-        .as_ref()
-        .chars()
-        .filter(|c| c.is_numeric())
-        .collect_vec();
+    let line = line.as_ref();
+    let line_digits = line.chars().filter(|c| c.is_ascii_digit()).collect_vec();
 
-    let &first_digit = line_digits.first().ok_or(AocError::NoDigits)?;
-    let &last_digit = line_digits.last().ok_or(AocError::NoDigits)?;
+    let no_digits = || AocError::NoDigits(line.to_owned());
+    let &first_digit = line_digits.first().ok_or_else(no_digits)?;
+    let &last_digit = line_digits.last().ok_or_else(no_digits)?;
 
     Ok((first_digit, last_digit))
 }
 
 fn get_number_from_digits((first, last): (char, char)) -> Result<usize, AocError> {
-    Ok(format!("{first}{last}").parse()?)
+    Ok(parse_int(&format!("{first}{last}"))?)
 }
 
 fn part2(input: &[String]) -> Result<usize, AocError> {
-    let first_last_digits = input.iter().map(get_first_and_last_digits_2);
-
-    let calibration_values: Vec<_> = first_last_digits
-        .map(|result| result.and_then(get_number_from_digits))
+    let first_last_digits: Vec<_> = input
+        .iter()
+        .map(get_first_and_last_digits_2)
         .try_collect()?;
 
-    Ok(calibration_values.iter().sum())
+    sum_calibration_values(&first_last_digits)
 }
 
 const DIGITS: [(&str, char); 18] = [
@@ -91,26 +153,75 @@ const DIGITS: [(&str, char); 18] = [
     ("nine", '9'),
 ];
 
+/// Like `DIGITS`, for inputs where zero counts as a digit too.
+#[cfg(test)]
+const DIGITS_WITH_ZERO: [(&str, char); 20] = [
+    ("0", '0'),
+    ("1", '1'),
+    ("2", '2'),
+    ("3", '3'),
+    ("4", '4'),
+    ("5", '5'),
+    ("6", '6'),
+    ("7", '7'),
+    ("8", '8'),
+    ("9", '9'),
+    ("zero", '0'),
+    ("one", '1'),
+    ("two", '2'),
+    ("three", '3'),
+    ("four", '4'),
+    ("five", '5'),
+    ("six", '6'),
+    ("seven", '7'),
+    ("eight", '8'),
+    ("nine", '9'),
+];
+
 fn get_first_and_last_digits_2<S: AsRef<str>>(line: S) -> Result<(char, char), AocError> {
-    let line = line.as_ref();
+    get_first_and_last_digits_with(line.as_ref(), &DIGITS)
+}
+
+/// Like `get_first_and_last_digits_2`, but with `0` and `zero` as digits too.
+#[cfg(test)]
+fn get_first_and_last_digits_2_with_zero<S: AsRef<str>>(line: S) -> Result<(char, char), AocError> {
+    get_first_and_last_digits_with(line.as_ref(), &DIGITS_WITH_ZERO)
+}
 
-    let first_digits = DIGITS
-        .into_iter()
-        .filter_map(|(pattern, digit)| line.find(pattern).map(|index| (index, digit)));
+/// The first and last digits of `line`, where each of `patterns` stands for its digit.
+fn get_first_and_last_digits_with(
+    line: &str,
+    patterns: &[(&str, char)],
+) -> Result<(char, char), AocError> {
+    scan_digits(line, patterns).ok_or_else(|| AocError::NoDigits(line.to_owned()))
+}
+
+/// Like `get_first_and_last_digits_with`, but ignoring case, e.g. `ONE` matches `one`. The
+/// patterns themselves should be lowercase.
+#[cfg(test)]
+fn get_first_and_last_digits_ignore_case(
+    line: &str,
+    patterns: &[(&str, char)],
+) -> Result<(char, char), AocError> {
+    scan_digits(&line.to_lowercase(), patterns).ok_or_else(|| AocError::NoDigits(line.to_owned()))
+}
 
-    let last_digits = DIGITS
-        .into_iter()
-        .filter_map(|(pattern, digit)| line.rfind(pattern).map(|index| (index, digit)));
+/// The first and last of `patterns` in `line`, in a single pass: at every byte, check whether a
+/// pattern starts there. Patterns may overlap, e.g. `oneight` is `1` then `8` with `DIGITS`.
+fn scan_digits(line: &str, patterns: &[(&str, char)]) -> Option<(char, char)> {
+    let bytes = line.as_bytes();
 
-    let (_, first) = first_digits
-        .min_by_key(|(index, _)| *index)
-        .ok_or(AocError::NoDigits)?;
+    let mut digits = (0..bytes.len()).filter_map(|index| {
+        patterns
+            .iter()
+            .find(|(pattern, _)| bytes[index..].starts_with(pattern.as_bytes()))
+            .map(|&(_, digit)| digit)
+    });
 
-    let (_, last) = last_digits
-        .max_by_key(|(index, _)| *index)
-        .ok_or(AocError::NoDigits)?;
+    let first = digits.next()?;
+    let last = digits.fold(first, |_, digit| digit);
 
-    Ok((first, last))
+    Some((first, last))
 }
 
 #[cfg(test)]
@@ -136,6 +247,78 @@ zoneight234
 7pqrstsixteen
 ";
 
+    #[test]
+    fn test_get_first_and_last_digits_ignores_non_ascii() {
+        assert_eq!(get_first_and_last_digits("²a1b٣2c³").unwrap(), ('1', '2'));
+    }
+
+    #[test]
+    fn test_scan_digits() {
+        let scan_digits = |line| scan_digits(line, &DIGITS);
+
+        assert_eq!(scan_digits("eightwothree"), Some(('8', '3')));
+        assert_eq!(scan_digits("zoneight"), Some(('1', '8')));
+        assert_eq!(scan_digits("xtwone3four"), Some(('2', '4')));
+        assert_eq!(scan_digits("7pqrstsixteen"), Some(('7', '6')));
+        assert_eq!(scan_digits("nine"), Some(('9', '9')));
+        assert_eq!(scan_digits("²éight٣"), None);
+        assert_eq!(scan_digits(""), None);
+    }
+
+    #[test]
+    fn test_get_first_and_last_digits_with() {
+        assert_eq!(get_first_and_last_digits_2("TWOone").unwrap(), ('1', '1'));
+        assert_eq!(
+            get_first_and_last_digits_ignore_case("TWOone", &DIGITS).unwrap(),
+            ('2', '1')
+        );
+
+        let german = [("eins", '1'), ("zwei", '2'), ("drei", '3')];
+        assert_eq!(
+            get_first_and_last_digits_with("zweiundeins", &german).unwrap(),
+            ('2', '1')
+        );
+        assert_eq!(
+            get_first_and_last_digits_ignore_case("Drei", &german).unwrap(),
+            ('3', '3')
+        );
+        assert!(matches!(
+            get_first_and_last_digits_with("two", &german),
+            Err(AocError::NoDigits(line)) if line == "two"
+        ));
+    }
+
+    #[test]
+    fn test_get_first_and_last_digits_2_with_zero() {
+        assert_eq!(
+            get_first_and_last_digits_2_with_zero("zero8").unwrap(),
+            ('0', '8')
+        );
+        assert_eq!(
+            get_first_and_last_digits_2_with_zero("a0bc7zerone").unwrap(),
+            ('0', '1')
+        );
+        assert_eq!(get_first_and_last_digits_2("zero8").unwrap(), ('8', '8'));
+        assert!(get_first_and_last_digits_2("0zero").is_err());
+    }
+
+    #[test]
+    fn test_no_digits_line() {
+        let input = to_lines("1abc2\n\ntreb7uchet\n");
+
+        assert!(matches!(part1(&input), Err(AocError::NoDigits(line)) if line.is_empty()));
+        assert!(matches!(part2(&input), Err(AocError::NoDigits(line)) if line.is_empty()));
+
+        let input = to_lines("1abc2\npqrstu\n");
+
+        assert!(matches!(part1(&input), Err(AocError::NoDigits(line)) if line == "pqrstu"));
+        assert!(matches!(part2(&input), Err(AocError::NoDigits(line)) if line == "pqrstu"));
+        assert!(matches!(
+            get_first_and_last_digits_ignore_case("PQRSTU", &DIGITS),
+            Err(AocError::NoDigits(line)) if line == "PQRSTU"
+        ));
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE_1);
@@ -149,4 +332,32 @@ zoneight234
 
         assert_eq!(part2(&input).unwrap(), 281);
     }
+
+    #[cfg(feature = "test-hooks")]
+    #[test]
+    fn test_main() {
+        aoc::set_test_input(to_lines(EXAMPLE_1));
+        assert!(main().is_ok());
+
+        aoc::set_test_input(to_lines("abc\n"));
+        assert!(matches!(main(), Err(AocError::NoDigits(line)) if line == "abc"));
+
+        aoc::clear_test_input();
+    }
+
+    #[cfg(feature = "timings")]
+    #[test]
+    fn test_solve() {
+        // The part 2 example has lines without any digit, which part 1 can't handle.
+        let input = to_lines(EXAMPLE_1);
+
+        let start = Instant::now();
+        let solved = Day01::solve(&input).unwrap();
+        let total = start.elapsed();
+
+        assert_eq!(solved.part1, "142");
+        assert_eq!(solved.part2, "142");
+        // Either may round down to zero with a coarse clock, but not add up to more than the total.
+        assert!(solved.parse_time + solved.solve_time <= total);
+    }
 }
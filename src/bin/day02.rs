@@ -1,37 +1,107 @@
-use std::{io, num::ParseIntError, str::FromStr};
+use std::{collections::HashMap, error, fmt, io, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{parse_int, split_label, Solution};
 use itertools::Itertools;
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
+pub(crate) enum AocError {
+    Common(aoc::Error),
+    InvalidColor(String),
     InvalidDrawnCubes(String),
     InvalidGame(String),
+    #[cfg(test)]
+    ScalingOverflow(usize),
+    CountOverflow(String),
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::InvalidColor(color) => write!(f, "invalid colour {color:?}"),
+            AocError::InvalidDrawnCubes(draw) => write!(f, "invalid draw {draw:?}"),
+            AocError::InvalidGame(game) => write!(f, "invalid game {game:?}"),
+            #[cfg(test)]
+            AocError::ScalingOverflow(factor) => {
+                write!(f, "scaling the cubes by {factor} overflowed")
+            }
+            AocError::CountOverflow(draw) => write!(f, "counting the cubes in {draw:?} overflowed"),
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+impl From<aoc::Error> for AocError {
+    fn from(e: aoc::Error) -> Self {
+        Self::Common(e)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day02.txt";
+pub(crate) struct Day02;
 
+impl Solution for Day02 {
+    const INPUT_FILE: &'static str = "day02.txt";
+
+    type Output = usize;
+    type Error = AocError;
+
+    fn part1(input: &[String]) -> Result<usize, AocError> {
+        part1(input)
+    }
+
+    fn part2(input: &[String]) -> Result<usize, AocError> {
+        part2(input)
+    }
+}
+
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
 fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+    aoc::run::<Day02>()
+}
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+impl Color {
+    const ALL: [Color; 3] = [Color::Red, Color::Green, Color::Blue];
 
-    Ok(())
+    fn name(&self) -> &'static str {
+        match self {
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Blue => "blue",
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::ALL
+            .into_iter()
+            .find(|color| color.name() == s)
+            .ok_or_else(|| AocError::InvalidColor(s.to_owned()))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -44,26 +114,84 @@ struct DrawnCubes {
 impl FromStr for DrawnCubes {
     type Err = AocError;
 
+    /// The empty string parses as no cubes at all, mirroring `Display`. A colour listed more
+    /// than once counts all of its cubes.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
+        let mut cubes = Self::default();
+
+        if s.is_empty() {
+            return Ok(cubes);
+        }
 
         for part in s.split(", ") {
             if let Some((amount, colour)) = part.split_whitespace().collect_tuple() {
-                let amount: usize = amount.parse()?;
-                match colour {
-                    "red" => red = amount,
-                    "green" => green = amount,
-                    "blue" => blue = amount,
-                    _ => return Err(AocError::InvalidDrawnCubes(s.to_owned())),
-                }
+                let amount: usize = parse_int(amount)?;
+                let total = cubes.get_mut(colour.parse()?);
+
+                *total = total
+                    .checked_add(amount)
+                    .ok_or_else(|| AocError::CountOverflow(s.to_owned()))?;
             } else {
                 return Err(AocError::InvalidDrawnCubes(s.to_owned()));
             }
         }
 
-        Ok(Self { red, green, blue })
+        Ok(cubes)
+    }
+}
+
+/// Renders the non-zero colours in the puzzle format, e.g. `4 red, 3 blue`. Drawing no cubes at
+/// all renders as the empty string.
+impl fmt::Display for DrawnCubes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = self
+            .iter_colors()
+            .map(|(colour, amount)| format!("{amount} {colour}"));
+
+        write!(f, "{}", parts.format(", "))
+    }
+}
+
+impl DrawnCubes {
+    /// The name and amount of every colour that was drawn at least once, in red, green, blue
+    /// order.
+    fn iter_colors(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        Color::ALL
+            .into_iter()
+            .map(|color| (color.name(), self.get(color)))
+            .filter(|&(_, amount)| amount > 0)
+    }
+
+    fn get(&self, color: Color) -> usize {
+        match color {
+            Color::Red => self.red,
+            Color::Green => self.green,
+            Color::Blue => self.blue,
+        }
+    }
+
+    fn get_mut(&mut self, color: Color) -> &mut usize {
+        match color {
+            Color::Red => &mut self.red,
+            Color::Green => &mut self.green,
+            Color::Blue => &mut self.blue,
+        }
+    }
+
+    /// Multiply every colour by `factor`, as if the bag were `factor` times bigger.
+    #[cfg(test)]
+    fn scaled(&self, factor: usize) -> Result<DrawnCubes, AocError> {
+        let scale = |amount: usize| {
+            amount
+                .checked_mul(factor)
+                .ok_or(AocError::ScalingOverflow(factor))
+        };
+
+        Ok(Self {
+            red: scale(self.red)?,
+            green: scale(self.green)?,
+            blue: scale(self.blue)?,
+        })
     }
 }
 
@@ -77,21 +205,45 @@ impl FromStr for Game {
     type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (prefix, draws) = s
-            .split(": ")
-            .collect_tuple()
-            .ok_or(AocError::InvalidGame(s.to_owned()))?;
+        let (prefix, draws) = split_label(s, ":").ok_or(AocError::InvalidGame(s.to_owned()))?;
 
         let id = prefix
             .strip_prefix("Game ")
             .ok_or(AocError::InvalidGame(s.to_owned()))?
-            .parse()?;
+            .trim();
+        let id = parse_int(id)?;
         let draws = draws.split("; ").map(|draw| draw.parse()).try_collect()?;
 
         Ok(Self { id, draws })
     }
 }
 
+impl Game {
+    /// The most cubes of each colour shown in a single draw, which is also the fewest cubes of each
+    /// colour the bag could have held.
+    fn max_per_color(&self) -> DrawnCubes {
+        self.draws
+            .iter()
+            .fold(Default::default(), |acc, draw| DrawnCubes {
+                red: acc.red.max(draw.red),
+                green: acc.green.max(draw.green),
+                blue: acc.blue.max(draw.blue),
+            })
+    }
+
+    /// The cubes of each colour shown over all the draws.
+    #[cfg(test)]
+    fn total_revealed(&self) -> DrawnCubes {
+        self.draws
+            .iter()
+            .fold(Default::default(), |acc, draw| DrawnCubes {
+                red: acc.red + draw.red,
+                green: acc.green + draw.green,
+                blue: acc.blue + draw.blue,
+            })
+    }
+}
+
 fn part1(input: &[String]) -> Result<usize, AocError> {
     let games: Vec<Game> = input.iter().map(|line| line.parse()).try_collect()?;
 
@@ -103,9 +255,46 @@ fn part1(input: &[String]) -> Result<usize, AocError> {
 }
 
 fn is_game_possible(game: &Game, red: usize, green: usize, blue: usize) -> bool {
-    game.draws
-        .iter()
-        .all(|draw| draw.red <= red && draw.green <= green && draw.blue <= blue)
+    game_violation(game, red, green, blue).is_none()
+}
+
+/// Whether no draw of `game` shows more cubes of a colour than its limit. There are no cubes of
+/// the colours missing from `limits`.
+#[cfg(test)]
+fn is_game_possible_with(game: &Game, limits: &HashMap<Color, usize>) -> bool {
+    game_violation_with(game, limits).is_none()
+}
+
+/// What makes `game` impossible with the given numbers of cubes, see `game_violation_with`.
+fn game_violation(
+    game: &Game,
+    red: usize,
+    green: usize,
+    blue: usize,
+) -> Option<(usize, Color, usize, usize)> {
+    let limits = HashMap::from([
+        (Color::Red, red),
+        (Color::Green, green),
+        (Color::Blue, blue),
+    ]);
+
+    game_violation_with(game, &limits)
+}
+
+/// The index of the first draw of `game` showing more cubes of a colour than its limit, along
+/// with that colour, how many cubes were shown and the limit. `None` if the game is possible.
+fn game_violation_with(
+    game: &Game,
+    limits: &HashMap<Color, usize>,
+) -> Option<(usize, Color, usize, usize)> {
+    game.draws.iter().enumerate().find_map(|(index, draw)| {
+        Color::ALL.into_iter().find_map(|color| {
+            let amount = draw.get(color);
+            let limit = limits.get(&color).copied().unwrap_or(0);
+
+            (amount > limit).then_some((index, color, amount, limit))
+        })
+    })
 }
 
 fn part2(input: &[String]) -> Result<usize, AocError> {
@@ -119,13 +308,19 @@ fn part2(input: &[String]) -> Result<usize, AocError> {
 }
 
 fn get_minimum_draw(game: &Game) -> DrawnCubes {
-    game.draws
-        .iter()
-        .fold(Default::default(), |acc, draw| DrawnCubes {
-            red: acc.red.max(draw.red),
-            green: acc.green.max(draw.green),
-            blue: acc.blue.max(draw.blue),
-        })
+    game.max_per_color()
+}
+
+/// The games for which `predicate` holds, in their original order.
+#[cfg(test)]
+fn games_matching(games: &[Game], predicate: impl Fn(&Game) -> bool) -> Vec<&Game> {
+    games.iter().filter(|game| predicate(game)).collect()
+}
+
+/// Holds for games where at least `n` red cubes were shown in a single draw.
+#[cfg(test)]
+fn min_red_at_least(n: usize) -> impl Fn(&Game) -> bool {
+    move |game| get_minimum_draw(game).red >= n
 }
 
 #[cfg(test)]
@@ -133,6 +328,7 @@ mod tests {
     use super::*;
 
     use aoc::to_lines;
+    use proptest::prelude::*;
 
     #[test]
     fn test_parse_game() {
@@ -162,6 +358,192 @@ mod tests {
         assert_eq!(game, expected_game);
     }
 
+    #[test]
+    fn test_drawn_cubes_display() {
+        let cubes = DrawnCubes {
+            red: 4,
+            green: 0,
+            blue: 3,
+        };
+
+        assert_eq!(cubes.to_string(), "4 red, 3 blue");
+        assert_eq!(DrawnCubes::default().to_string(), "");
+        assert_eq!("".parse::<DrawnCubes>().unwrap(), DrawnCubes::default());
+    }
+
+    #[test]
+    fn test_parse_drawn_cubes_colors() {
+        let cubes: DrawnCubes = "3 red, 2 blue, 4 red".parse().unwrap();
+        let expected_cubes = DrawnCubes {
+            red: 7,
+            green: 0,
+            blue: 2,
+        };
+
+        assert_eq!(cubes, expected_cubes);
+        assert!(matches!(
+            "3 red, 2 purple".parse::<DrawnCubes>(),
+            Err(AocError::InvalidColor(color)) if color == "purple"
+        ));
+        assert!(matches!(
+            "3 red, blue".parse::<DrawnCubes>(),
+            Err(AocError::InvalidDrawnCubes(_))
+        ));
+        assert!(matches!(
+            format!("{} red, 1 red", usize::MAX).parse::<DrawnCubes>(),
+            Err(AocError::CountOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = "3 red, 2 purple".parse::<DrawnCubes>().unwrap_err();
+        assert_eq!(error.to_string(), r#"invalid colour "purple""#);
+
+        let error = "Game 1 3 red".parse::<Game>().unwrap_err();
+        assert_eq!(error.to_string(), r#"invalid game "Game 1 3 red""#);
+    }
+
+    #[test]
+    fn test_game_max_per_color_and_total_revealed() {
+        let game: Game = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            game.max_per_color(),
+            DrawnCubes {
+                red: 4,
+                green: 2,
+                blue: 6,
+            }
+        );
+        assert_eq!(get_minimum_draw(&game), game.max_per_color());
+        assert_eq!(
+            game.total_revealed(),
+            DrawnCubes {
+                red: 5,
+                green: 4,
+                blue: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn test_game_violation() {
+        let input = to_lines(EXAMPLE);
+        let games: Vec<Game> = input.iter().map(|line| line.parse()).try_collect().unwrap();
+
+        assert_eq!(
+            game_violation(&games[2], 12, 13, 14),
+            Some((0, Color::Red, 20, 12))
+        );
+        assert_eq!(
+            game_violation(&games[3], 12, 13, 14),
+            Some((2, Color::Red, 14, 12))
+        );
+        assert_eq!(game_violation(&games[0], 12, 13, 14), None);
+    }
+
+    #[test]
+    fn test_is_game_possible_with() {
+        let input = to_lines(EXAMPLE);
+        let games: Vec<Game> = input.iter().map(|line| line.parse()).try_collect().unwrap();
+
+        let limits = HashMap::from([(Color::Red, 12), (Color::Green, 13), (Color::Blue, 14)]);
+        let possible = games
+            .iter()
+            .filter(|game| is_game_possible_with(game, &limits))
+            .map(|game| game.id)
+            .collect_vec();
+        assert_eq!(possible, vec![1, 2, 5]);
+
+        // Every game shows some green cubes, so none is possible without any.
+        let limits = HashMap::from([(Color::Red, 100), (Color::Blue, 100)]);
+        assert!(!games
+            .iter()
+            .any(|game| is_game_possible_with(game, &limits)));
+    }
+
+    #[test]
+    fn test_drawn_cubes_iter_colors() {
+        let cubes = DrawnCubes {
+            red: 4,
+            green: 0,
+            blue: 3,
+        };
+
+        assert_eq!(
+            cubes.iter_colors().collect_vec(),
+            vec![("red", 4), ("blue", 3)]
+        );
+        assert_eq!(DrawnCubes::default().iter_colors().count(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_drawn_cubes_round_trip(red in 0..100usize, green in 0..100usize, blue in 0..100usize) {
+            let cubes = DrawnCubes { red, green, blue };
+
+            prop_assert_eq!(cubes.to_string().parse::<DrawnCubes>().unwrap(), cubes);
+        }
+    }
+
+    #[test]
+    fn test_drawn_cubes_scaled() {
+        let cubes = DrawnCubes {
+            red: 12,
+            green: 13,
+            blue: 14,
+        };
+        let expected_cubes = DrawnCubes {
+            red: 36,
+            green: 39,
+            blue: 42,
+        };
+
+        assert_eq!(cubes.scaled(3).unwrap(), expected_cubes);
+        assert_eq!(cubes.scaled(0).unwrap(), DrawnCubes::default());
+    }
+
+    #[test]
+    fn test_drawn_cubes_scaled_overflow() {
+        let cubes = DrawnCubes {
+            red: 0,
+            green: 2,
+            blue: 1,
+        };
+
+        assert!(matches!(
+            cubes.scaled(usize::MAX),
+            Err(AocError::ScalingOverflow(usize::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_parse_game_space_before_colon() {
+        let game: Game = "Game 1 : 3 blue".parse().unwrap();
+        let expected_game = Game {
+            id: 1,
+            draws: vec![DrawnCubes {
+                red: 0,
+                green: 0,
+                blue: 3,
+            }],
+        };
+
+        assert_eq!(game, expected_game);
+
+        assert!(matches!(
+            "Game 1 3 blue".parse::<Game>(),
+            Err(AocError::InvalidGame(_))
+        ));
+        assert!(matches!(
+            "Gaem 1 : 3 blue".parse::<Game>(),
+            Err(AocError::InvalidGame(_))
+        ));
+    }
+
     // Make sure to remove any extra indentation (otherwise it will be part of the string)
     const EXAMPLE: &str = "\
 Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
@@ -171,6 +553,24 @@ Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
 Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green    
 ";
 
+    #[test]
+    fn test_games_matching() {
+        let input = to_lines(EXAMPLE);
+        let games: Vec<Game> = input.iter().map(|line| line.parse()).try_collect().unwrap();
+
+        let ids = |games: Vec<&Game>| games.iter().map(|game| game.id).collect_vec();
+
+        assert_eq!(
+            ids(games_matching(&games, min_red_at_least(6))),
+            vec![3, 4, 5]
+        );
+        assert_eq!(
+            ids(games_matching(&games, min_red_at_least(0))),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert_eq!(ids(games_matching(&games, min_red_at_least(21))), vec![]);
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
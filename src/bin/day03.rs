@@ -1,38 +1,45 @@
-use std::{collections::HashMap, io, num::ParseIntError};
+use std::{collections::HashMap, env, iter};
 
-use aoc::read_lines;
+use aoc::{parse_int, read_lines, Error, Solution};
 use itertools::Itertools;
 
-#[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
-}
+pub(crate) struct Day03;
+
+impl Solution for Day03 {
+    const INPUT_FILE: &'static str = "day03.txt";
+
+    type Output = usize;
+    type Error = Error;
 
-impl From<io::Error> for AocError {
-    fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+    fn part1(input: &[String]) -> Result<usize, Error> {
+        part1(input)
     }
-}
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+    fn part2(input: &[String]) -> Result<usize, Error> {
+        part2(input)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day03.txt";
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
+fn main() -> Result<(), Error> {
+    if env::args().any(|arg| arg == "--stats") {
+        let input = read_lines(aoc::cli_input_path::<Day03>())?;
+        let schematic = parse_engine_schematic(&input)?;
+        let counts = count_gear_outcomes(&schematic);
 
-fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+        println!("{} gears", counts.valid);
+        println!("{} rejected for touching too few numbers", counts.too_few);
+        println!("{} rejected for touching too many numbers", counts.too_many);
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+        return Ok(());
+    }
 
-    Ok(())
+    aoc::run::<Day03>()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Ordered by value first, then by position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct EngineSchematicNumber {
     number: usize,
     x_start: usize,
@@ -46,7 +53,70 @@ struct EngineSchematic {
     symbols: HashMap<(usize, usize), char>,
 }
 
-fn parse_engine_schematic(input: &[String]) -> Result<EngineSchematic, AocError> {
+impl EngineSchematic {
+    /// The numbers adjacent to a symbol.
+    fn part_numbers(&self) -> impl Iterator<Item = &EngineSchematicNumber> {
+        self.numbers
+            .iter()
+            .filter(|number| is_adjacent_to_symbol(**number, &self.symbols))
+    }
+
+    /// The values of the part numbers, largest first.
+    #[cfg(test)]
+    fn part_numbers_sorted(&self) -> Vec<usize> {
+        self.part_numbers()
+            .sorted_unstable_by(|a, b| b.cmp(a))
+            .map(|number| number.number)
+            .collect()
+    }
+
+    /// Describe every number and symbol that differs between `self` and `other`. Numbers are
+    /// compared with their multiplicity, whatever their order.
+    #[cfg(test)]
+    fn diff(&self, other: &EngineSchematic) -> Vec<String> {
+        let mut discrepancies = vec![];
+
+        let self_counts = self.numbers.iter().counts();
+        let other_counts = other.numbers.iter().counts();
+        let numbers = self_counts.keys().chain(other_counts.keys()).unique();
+
+        for number in numbers.sorted() {
+            match (self_counts.get(number), other_counts.get(number)) {
+                (Some(a), Some(b)) if a != b => discrepancies.push(format!(
+                    "number {number:?} appears {a} times in self but {b} times in other"
+                )),
+                (Some(_), None) => {
+                    discrepancies.push(format!("number {number:?} is missing from other"))
+                }
+                (None, Some(_)) => {
+                    discrepancies.push(format!("number {number:?} is missing from self"))
+                }
+                _ => (),
+            }
+        }
+
+        let positions = self.symbols.keys().chain(other.symbols.keys()).unique();
+
+        for position in positions.sorted() {
+            match (self.symbols.get(position), other.symbols.get(position)) {
+                (Some(a), Some(b)) if a != b => discrepancies.push(format!(
+                    "symbol at {position:?} is '{a}' in self but '{b}' in other"
+                )),
+                (Some(a), None) => discrepancies.push(format!(
+                    "symbol '{a}' at {position:?} is missing from other"
+                )),
+                (None, Some(b)) => {
+                    discrepancies.push(format!("symbol '{b}' at {position:?} is missing from self"))
+                }
+                _ => (),
+            }
+        }
+
+        discrepancies
+    }
+}
+
+fn parse_engine_schematic(input: &[String]) -> Result<EngineSchematic, Error> {
     let mut numbers = vec![];
     let mut symbols = HashMap::new();
 
@@ -54,6 +124,9 @@ fn parse_engine_schematic(input: &[String]) -> Result<EngineSchematic, AocError>
         let mut current_number_span: Option<(String, usize)> = None;
 
         for (x, c) in line.chars().enumerate() {
+            // Whitespace (e.g. trailing spaces) is a gap, just like `.`.
+            let c = if c.is_whitespace() { '.' } else { c };
+
             current_number_span = match (current_number_span, c) {
                 (None, '.') => None,
                 (None, n) if n.is_ascii_digit() => Some((String::from(n), x)),
@@ -64,7 +137,7 @@ fn parse_engine_schematic(input: &[String]) -> Result<EngineSchematic, AocError>
                 }
                 (Some((span, x_start)), '.') => {
                     numbers.push(EngineSchematicNumber {
-                        number: span.parse()?,
+                        number: parse_int(&span)?,
                         x_start,
                         x_end: x - 1,
                         y,
@@ -80,7 +153,7 @@ fn parse_engine_schematic(input: &[String]) -> Result<EngineSchematic, AocError>
                 (Some((span, x_start)), s) => {
                     symbols.insert((x, y), s);
                     numbers.push(EngineSchematicNumber {
-                        number: span.parse()?,
+                        number: parse_int(&span)?,
                         x_start,
                         x_end: x - 1,
                         y,
@@ -93,9 +166,9 @@ fn parse_engine_schematic(input: &[String]) -> Result<EngineSchematic, AocError>
 
         if let Some((span, x_start)) = current_number_span {
             numbers.push(EngineSchematicNumber {
-                number: span.parse()?,
+                number: parse_int(&span)?,
                 x_start,
-                x_end: line.chars().count() - 1,
+                x_end: x_start + span.len() - 1,
                 y,
             });
         }
@@ -104,74 +177,174 @@ fn parse_engine_schematic(input: &[String]) -> Result<EngineSchematic, AocError>
     Ok(EngineSchematic { numbers, symbols })
 }
 
-fn part1(input: &[String]) -> Result<usize, AocError> {
-    let schematic = parse_engine_schematic(input)?;
-
-    let engine_part_numbers = schematic
-        .numbers
+/// Parse the schematic surrounded by a border of `.`, so that every coordinate is shifted by one
+/// and the neighbours of every number are within the grid.
+#[cfg(test)]
+fn parse_engine_schematic_padded(input: &[String]) -> Result<EngineSchematic, Error> {
+    let width = input
         .iter()
-        .filter(|number| is_adjacent_to_symbol(**number, &schematic.symbols));
-
-    Ok(engine_part_numbers.map(|number| number.number).sum())
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let border = ".".repeat(width);
+
+    let padded = iter::once(border.clone())
+        .chain(input.iter().map(|line| format!(".{line}.")))
+        .chain(iter::once(border))
+        .collect_vec();
+
+    parse_engine_schematic(&padded)
 }
 
-fn get_neighbours(number: EngineSchematicNumber) -> Vec<(usize, usize)> {
-    let mut neighbours = vec![];
-
-    if number.x_start > 0 && number.y > 0 {
-        // top-left
-        neighbours.push((number.x_start - 1, number.y - 1));
-    }
-
-    if number.x_start > 0 {
-        // middle-left
-        neighbours.push((number.x_start - 1, number.y));
-        // bottom-left
-        neighbours.push((number.x_start - 1, number.y + 1));
-    }
+fn part1(input: &[String]) -> Result<usize, Error> {
+    let schematic = parse_engine_schematic(input)?;
 
-    if number.y > 0 {
-        // top & top-right
-        neighbours.extend((number.x_start..=(number.x_end + 1)).map(|x| (x, number.y - 1)));
-    }
+    Ok(schematic.part_numbers().map(|number| number.number).sum())
+}
 
+/// The positions around `number` (those that would be negative are left out), without
+/// allocating.
+fn get_neighbours(number: EngineSchematicNumber) -> impl Iterator<Item = (usize, usize)> {
+    let EngineSchematicNumber {
+        x_start, x_end, y, ..
+    } = number;
+    let left = x_start.checked_sub(1);
+    let above = y.checked_sub(1);
+
+    // top-left
+    let top_left = left.zip(above);
+    // middle-left & bottom-left
+    let left_column = left.into_iter().flat_map(move |x| [(x, y), (x, y + 1)]);
+    // top & top-right
+    let top = above
+        .into_iter()
+        .flat_map(move |y| (x_start..=(x_end + 1)).map(move |x| (x, y)));
     // bottom & bottom-right
-    neighbours.extend((number.x_start..=(number.x_end + 1)).map(|x| (x, number.y + 1)));
-
+    let bottom = (x_start..=(x_end + 1)).map(move |x| (x, y + 1));
     // middle-right
-    neighbours.push((number.x_end + 1, number.y));
+    let right = iter::once((x_end + 1, y));
 
-    neighbours
+    top_left
+        .into_iter()
+        .chain(left_column)
+        .chain(top)
+        .chain(bottom)
+        .chain(right)
+}
+
+/// The first symbol around `number`, in the order of `get_neighbours`, with its position.
+fn adjacent_symbol(
+    number: EngineSchematicNumber,
+    symbols: &HashMap<(usize, usize), char>,
+) -> Option<(char, (usize, usize))> {
+    get_neighbours(number).find_map(|pos| symbols.get(&pos).map(|&symbol| (symbol, pos)))
 }
 
 fn is_adjacent_to_symbol(
     number: EngineSchematicNumber,
     symbols: &HashMap<(usize, usize), char>,
 ) -> bool {
-    get_neighbours(number)
-        .into_iter()
-        .any(|pos| symbols.contains_key(&pos))
+    adjacent_symbol(number, symbols).is_some()
 }
 
-fn part2(input: &[String]) -> Result<usize, AocError> {
+fn part2(input: &[String]) -> Result<usize, Error> {
     let schematic = parse_engine_schematic(input)?;
 
-    let potential_gears = schematic.symbols.into_iter().filter(|(_, s)| *s == '*');
-
-    let gear_ratios =
-        potential_gears.filter_map(|(gear, _)| get_gear_ratio(gear, &schematic.numbers));
+    let gear_ratios = symbol_groups(&schematic, '*', 2)
+        .into_iter()
+        .map(|gear| gear.into_iter().product::<usize>());
 
     Ok(gear_ratios.sum())
 }
 
-fn get_gear_ratio(gear: (usize, usize), numbers: &[EngineSchematicNumber]) -> Option<usize> {
-    let neighbouring_numbers = numbers
+/// For every position around some numbers, the indices of those numbers in `numbers`, so that a
+/// gear can find the numbers it touches without going through all of them.
+fn neighbour_index(numbers: &[EngineSchematicNumber]) -> HashMap<(usize, usize), Vec<usize>> {
+    let mut index: HashMap<_, Vec<_>> = HashMap::new();
+
+    for (i, number) in numbers.iter().enumerate() {
+        for position in get_neighbours(*number) {
+            index.entry(position).or_default().push(i);
+        }
+    }
+
+    index
+}
+
+/// The numbers around every `symbol` that touches exactly `k` numbers, in reading order of the
+/// symbols and then of the numbers around each of them.
+fn symbol_groups(schematic: &EngineSchematic, symbol: char, k: usize) -> Vec<Vec<usize>> {
+    let index = neighbour_index(&schematic.numbers);
+
+    schematic
+        .symbols
+        .iter()
+        .filter(|(_, &s)| s == symbol)
+        .map(|(&(x, y), _)| (y, x))
+        .sorted()
+        .filter_map(|(y, x)| {
+            let group = index
+                .get(&(x, y))
+                .map_or(&[][..], Vec::as_slice)
+                .iter()
+                .map(|&i| schematic.numbers[i].number)
+                .collect_vec();
+
+            (group.len() == k).then_some(group)
+        })
+        .collect()
+}
+
+/// What a `*` amounts to, depending on how many numbers it touches: only exactly two make a gear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GearOutcome {
+    TooFew,
+    Valid(usize),
+    TooMany,
+}
+
+/// `index` is the `neighbour_index` of `numbers`.
+fn get_gear_ratio(
+    gear: (usize, usize),
+    numbers: &[EngineSchematicNumber],
+    index: &HashMap<(usize, usize), Vec<usize>>,
+) -> GearOutcome {
+    let neighbouring_numbers = index
+        .get(&gear)
+        .map_or(&[][..], Vec::as_slice)
         .iter()
-        .filter(|number| get_neighbours(**number).into_iter().any(|pos| pos == gear));
+        .map(|&i| &numbers[i])
+        .collect_vec();
+
+    match neighbouring_numbers.as_slice() {
+        [number1, number2] => GearOutcome::Valid(number1.number * number2.number),
+        [] | [_] => GearOutcome::TooFew,
+        _ => GearOutcome::TooMany,
+    }
+}
+
+/// How many `*` there are for each `GearOutcome`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct GearCounts {
+    valid: usize,
+    too_few: usize,
+    too_many: usize,
+}
+
+fn count_gear_outcomes(schematic: &EngineSchematic) -> GearCounts {
+    let index = neighbour_index(&schematic.numbers);
+    let mut counts = GearCounts::default();
+
+    for (&gear, _) in schematic.symbols.iter().filter(|(_, &s)| s == '*') {
+        match get_gear_ratio(gear, &schematic.numbers, &index) {
+            GearOutcome::TooFew => counts.too_few += 1,
+            GearOutcome::Valid(_) => counts.valid += 1,
+            GearOutcome::TooMany => counts.too_many += 1,
+        }
+    }
 
-    neighbouring_numbers
-        .collect_tuple()
-        .map(|(number1, number2)| (number1.number * number2.number))
+    counts
 }
 
 #[cfg(test)]
@@ -230,6 +403,267 @@ mod tests {
         assert_eq!(schematic, expected_schematic);
     }
 
+    /// The parser as it was before the refactor that made whitespace a gap, to check the current
+    /// one against.
+    fn parse_engine_schematic_baseline(input: &[String]) -> Result<EngineSchematic, Error> {
+        let mut numbers = vec![];
+        let mut symbols = HashMap::new();
+
+        for (y, line) in input.iter().enumerate() {
+            let mut current_number_span: Option<(String, usize)> = None;
+
+            for (x, c) in line.chars().enumerate() {
+                current_number_span = match (current_number_span, c) {
+                    (None, '.') => None,
+                    (None, n) if n.is_ascii_digit() => Some((String::from(n), x)),
+                    (None, s) => {
+                        symbols.insert((x, y), s);
+
+                        None
+                    }
+                    (Some((span, x_start)), '.') => {
+                        numbers.push(EngineSchematicNumber {
+                            number: parse_int(&span)?,
+                            x_start,
+                            x_end: x - 1,
+                            y,
+                        });
+
+                        None
+                    }
+                    (Some((mut span, x_start)), n) if n.is_ascii_digit() => {
+                        span.push(n);
+
+                        Some((span, x_start))
+                    }
+                    (Some((span, x_start)), s) => {
+                        symbols.insert((x, y), s);
+                        numbers.push(EngineSchematicNumber {
+                            number: parse_int(&span)?,
+                            x_start,
+                            x_end: x - 1,
+                            y,
+                        });
+
+                        None
+                    }
+                }
+            }
+
+            if let Some((span, x_start)) = current_number_span {
+                numbers.push(EngineSchematicNumber {
+                    number: parse_int(&span)?,
+                    x_start,
+                    x_end: line.chars().count() - 1,
+                    y,
+                });
+            }
+        }
+
+        Ok(EngineSchematic { numbers, symbols })
+    }
+
+    #[test]
+    fn test_parse_engine_schematic_matches_baseline() {
+        let edge_cases = [
+            EXAMPLE,
+            "",
+            "7",
+            "12\n\n3.",
+            "..5\n5..",
+            "1*2#3",
+            "*\n123\n..#",
+            "é12.\n.3é",
+            "12\n345678\n9",
+            "12.12\n12.12",
+        ];
+
+        for input in edge_cases {
+            let input = to_lines(input);
+            let schematic = parse_engine_schematic(&input).unwrap();
+            let baseline = parse_engine_schematic_baseline(&input).unwrap();
+
+            assert_eq!(schematic.diff(&baseline), Vec::<String>::new(), "{input:?}");
+        }
+
+        // Whitespace used to be a symbol.
+        let input = to_lines("12 \n.*.");
+        let schematic = parse_engine_schematic(&input).unwrap();
+        let baseline = parse_engine_schematic_baseline(&input).unwrap();
+
+        assert_eq!(
+            schematic.diff(&baseline),
+            vec!["symbol ' ' at (2, 0) is missing from self"]
+        );
+    }
+
+    #[test]
+    fn test_engine_schematic_diff() {
+        let schematic = parse_engine_schematic(&to_lines(EXAMPLE)).unwrap();
+        let other_schematic = parse_engine_schematic(&to_lines("467..114..\n...#......")).unwrap();
+        let discrepancies = schematic.diff(&other_schematic);
+
+        assert!(
+            discrepancies.contains(&"symbol at (3, 1) is '*' in self but '#' in other".to_owned())
+        );
+        assert!(discrepancies.contains(&"symbol '#' at (6, 3) is missing from other".to_owned()));
+        assert!(!discrepancies.iter().any(|d| d.contains("number: 467")));
+        assert!(discrepancies.iter().any(|d| d.contains("number: 35")));
+
+        // The same number twice is not the same as once.
+        let number = EngineSchematicNumber {
+            number: 12,
+            x_start: 0,
+            x_end: 1,
+            y: 0,
+        };
+        let once = EngineSchematic {
+            numbers: vec![number],
+            symbols: HashMap::new(),
+        };
+        let twice = EngineSchematic {
+            numbers: vec![number, number],
+            symbols: HashMap::new(),
+        };
+
+        assert_eq!(
+            once.diff(&twice),
+            vec![format!(
+                "number {number:?} appears 1 times in self but 2 times in other"
+            )]
+        );
+        assert_eq!(twice.diff(&twice), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parse_engine_schematic_trailing_whitespace() {
+        let input = to_lines("..467  \n...*..\n.35");
+
+        let schematic = parse_engine_schematic(&input).unwrap();
+        let expected_schematic = EngineSchematic {
+            numbers: vec![
+                EngineSchematicNumber {
+                    number: 467,
+                    x_start: 2,
+                    x_end: 4,
+                    y: 0,
+                },
+                EngineSchematicNumber {
+                    number: 35,
+                    x_start: 1,
+                    x_end: 2,
+                    y: 2,
+                },
+            ],
+            symbols: HashMap::from([((3, 1), '*')]),
+        };
+
+        assert_eq!(schematic, expected_schematic);
+    }
+
+    #[test]
+    fn test_part_numbers_sorted() {
+        let schematic = parse_engine_schematic(&to_lines(EXAMPLE)).unwrap();
+
+        assert_eq!(
+            schematic.part_numbers_sorted(),
+            vec![755, 664, 633, 617, 598, 592, 467, 35]
+        );
+    }
+
+    #[test]
+    fn test_parse_engine_schematic_padded() {
+        let input = to_lines(EXAMPLE);
+        let schematic = parse_engine_schematic(&input).unwrap();
+        let padded = parse_engine_schematic_padded(&input).unwrap();
+
+        let shifted_numbers = schematic
+            .numbers
+            .iter()
+            .map(|number| EngineSchematicNumber {
+                x_start: number.x_start + 1,
+                x_end: number.x_end + 1,
+                y: number.y + 1,
+                ..*number
+            })
+            .collect_vec();
+        assert_eq!(padded.numbers, shifted_numbers);
+
+        assert_eq!(padded.part_numbers().map(|n| n.number).sum::<usize>(), 4361);
+
+        let gears = symbol_groups(&padded, '*', 2);
+        assert_eq!(gears, symbol_groups(&schematic, '*', 2));
+        assert_eq!(
+            gears
+                .iter()
+                .map(|gear| gear.iter().product::<usize>())
+                .sum::<usize>(),
+            467835
+        );
+    }
+
+    #[test]
+    fn test_get_gear_ratio() {
+        let ratio = |input: &str| {
+            let schematic = parse_engine_schematic(&to_lines(input)).unwrap();
+            let (&gear, _) = schematic.symbols.iter().find(|(_, s)| **s == '*').unwrap();
+
+            let index = neighbour_index(&schematic.numbers);
+
+            get_gear_ratio(gear, &schematic.numbers, &index)
+        };
+
+        assert_eq!(ratio("12*..\n....."), GearOutcome::TooFew);
+        assert_eq!(ratio("12*..\n...34"), GearOutcome::Valid(408));
+        assert_eq!(ratio("12*5.\n...34"), GearOutcome::TooMany);
+        assert_eq!(ratio("..*..\n....."), GearOutcome::TooFew);
+    }
+
+    #[test]
+    fn test_count_gear_outcomes() {
+        let schematic = parse_engine_schematic(&to_lines(EXAMPLE)).unwrap();
+        let expected_counts = GearCounts {
+            valid: 2,
+            too_few: 1,
+            too_many: 0,
+        };
+
+        assert_eq!(count_gear_outcomes(&schematic), expected_counts);
+
+        let schematic = parse_engine_schematic(&to_lines("12*5.\n...34\n*....")).unwrap();
+        let expected_counts = GearCounts {
+            valid: 0,
+            too_few: 1,
+            too_many: 1,
+        };
+
+        assert_eq!(count_gear_outcomes(&schematic), expected_counts);
+    }
+
+    #[test]
+    fn test_neighbour_index() {
+        let input = to_lines("12*5.\n...34\n");
+        let schematic = parse_engine_schematic(&input).unwrap();
+        let index = neighbour_index(&schematic.numbers);
+
+        let touching = |position| {
+            index[&position]
+                .iter()
+                .map(|&i| schematic.numbers[i].number)
+                .sorted()
+                .collect_vec()
+        };
+
+        assert_eq!(touching((2, 0)), vec![5, 12, 34]);
+        assert_eq!(touching((2, 1)), vec![5, 12, 34]);
+        assert_eq!(touching((0, 1)), vec![12]);
+        assert!(!index.contains_key(&(0, 0)));
+
+        // The `*` touching three numbers is no gear, only the one below is.
+        let input = to_lines("12*5.\n...34\n.2*..\n.....\n");
+        assert_eq!(part2(&input).unwrap(), 2 * 34);
+    }
+
     #[test]
     fn test_get_neighbours_corner() {
         let number = EngineSchematicNumber {
@@ -238,7 +672,7 @@ mod tests {
             x_end: 0,
             y: 0,
         };
-        let neighbours = get_neighbours(number);
+        let neighbours = get_neighbours(number).collect_vec();
 
         assert_eq!(neighbours, vec![(0, 1), (1, 1), (1, 0)]);
     }
@@ -251,11 +685,34 @@ mod tests {
             x_end: 3,
             y: 1,
         };
-        let neighbours = get_neighbours(number);
+        let neighbours = get_neighbours(number).collect_vec();
 
         assert_eq!(neighbours.len(), 12);
     }
 
+    #[test]
+    fn test_get_neighbours_bounding_box() {
+        for (x_start, width, y) in itertools::iproduct!(0..3, 0..3, 0..3) {
+            let number = EngineSchematicNumber {
+                number: 1,
+                x_start,
+                x_end: x_start + width,
+                y,
+            };
+
+            // Every non-negative position in the box around the number, except the number itself.
+            let expected = itertools::iproduct!(
+                x_start.saturating_sub(1)..=number.x_end + 1,
+                y.saturating_sub(1)..=y + 1
+            )
+            .filter(|&(x, y)| y != number.y || x < x_start || x > number.x_end)
+            .sorted()
+            .collect_vec();
+
+            assert_eq!(get_neighbours(number).sorted().collect_vec(), expected);
+        }
+    }
+
     // Make sure to remove any extra indentation (otherwise it will be part of the string)
     const EXAMPLE: &str = "\
 467..114..
@@ -270,6 +727,42 @@ mod tests {
 .664.598..
 ";
 
+    #[test]
+    fn test_adjacent_symbol() {
+        let input = to_lines(EXAMPLE);
+        let schematic = parse_engine_schematic(&input).unwrap();
+        let adjacent = |n| {
+            let number = schematic.numbers.iter().find(|number| number.number == n);
+
+            adjacent_symbol(*number.unwrap(), &schematic.symbols)
+        };
+
+        assert_eq!(adjacent(467), Some(('*', (3, 1))));
+        assert_eq!(adjacent(592), Some(('+', (5, 5))));
+        assert_eq!(adjacent(114), None);
+        assert_eq!(adjacent(58), None);
+    }
+
+    #[test]
+    fn test_symbol_groups() {
+        let input = to_lines(EXAMPLE);
+        let schematic = parse_engine_schematic(&input).unwrap();
+
+        let gears = symbol_groups(&schematic, '*', 2);
+        assert_eq!(gears, vec![vec![467, 35], vec![755, 598]]);
+        assert_eq!(
+            gears
+                .iter()
+                .map(|gear| gear.iter().product::<usize>())
+                .sum::<usize>(),
+            467835
+        );
+
+        assert_eq!(symbol_groups(&schematic, '*', 1), vec![vec![617]]);
+        assert_eq!(symbol_groups(&schematic, '#', 1), vec![vec![633]]);
+        assert_eq!(symbol_groups(&schematic, '$', 2), Vec::<Vec<usize>>::new());
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
@@ -1,50 +1,92 @@
 use std::{
-    collections::{HashMap, HashSet},
-    io,
-    num::ParseIntError,
+    collections::BTreeMap,
+    env, error, fmt, io, iter,
+    ops::Bound::{Excluded, Included},
     str::FromStr,
 };
 
-use aoc::read_lines;
+use aoc::{parse_int, read_lines, Solution};
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
+pub(crate) enum AocError {
+    Common(aoc::Error),
     InvalidScratchCard(String),
+    DuplicateCard(usize),
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::InvalidScratchCard(card) => write!(f, "invalid scratch card {card:?}"),
+            AocError::DuplicateCard(id) => write!(f, "card {id} is listed more than once"),
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+impl From<aoc::Error> for AocError {
+    fn from(e: aoc::Error) -> Self {
+        Self::Common(e)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day04.txt";
+pub(crate) struct Day04;
+
+impl Solution for Day04 {
+    const INPUT_FILE: &'static str = "day04.txt";
 
+    type Output = usize;
+    type Error = AocError;
+
+    fn part1(input: &[String]) -> Result<usize, AocError> {
+        part1(input)
+    }
+
+    fn part2(input: &[String]) -> Result<usize, AocError> {
+        part2(input)
+    }
+}
+
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
 fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+    if env::args().any(|arg| arg == "--stats") {
+        let input = read_lines(aoc::cli_input_path::<Day04>())?;
+        let cards: Vec<ScratchCard> = input.iter().map(|line| line.parse()).try_collect()?;
+
+        for (matches, count) in match_histogram(&cards) {
+            println!("{matches} matches: {count} cards");
+        }
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+        return Ok(());
+    }
 
-    Ok(())
+    aoc::run::<Day04>()
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct ScratchCard {
     id: usize,
-    left_numbers: HashSet<usize>,
-    right_numbers: HashSet<usize>,
+    left_numbers: Vec<usize>,
+    right_numbers: Vec<usize>,
 }
 
 impl FromStr for ScratchCard {
@@ -59,18 +101,12 @@ impl FromStr for ScratchCard {
             .map(|caps| caps.extract())
             .ok_or(AocError::InvalidScratchCard(s.to_owned()))?;
 
-        let id = id.parse()?;
+        let id = parse_int(id)?;
 
         static WHITESPACE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
 
-        let left_numbers = WHITESPACE_REGEX
-            .split(left)
-            .map(|n| n.parse())
-            .try_collect()?;
-        let right_numbers = WHITESPACE_REGEX
-            .split(right)
-            .map(|n| n.parse())
-            .try_collect()?;
+        let left_numbers = WHITESPACE_REGEX.split(left).map(parse_int).try_collect()?;
+        let right_numbers = WHITESPACE_REGEX.split(right).map(parse_int).try_collect()?;
 
         Ok(Self {
             id,
@@ -81,19 +117,49 @@ impl FromStr for ScratchCard {
 }
 
 impl ScratchCard {
+    /// The winning numbers that we have, in the order they are listed as winning. A number is
+    /// repeated for every copy of it that we have, however many times it is listed as winning.
+    fn matching_numbers(&self) -> Vec<usize> {
+        self.left_numbers
+            .iter()
+            .unique()
+            .flat_map(|number| {
+                let copies = self.right_numbers.iter().filter(|&n| n == number).count();
+
+                iter::repeat_n(*number, copies)
+            })
+            .collect()
+    }
+
     fn count_matches(&self) -> usize {
-        self.left_numbers.intersection(&self.right_numbers).count()
+        self.matching_numbers().len()
     }
 
     fn get_points(&self) -> usize {
-        let number_matches = self.count_matches();
+        get_points_for_matches(self.count_matches())
+    }
+}
 
-        if number_matches > 0 {
-            2usize.pow((number_matches - 1) as u32)
-        } else {
-            0
-        }
+/// One point for the first match, doubled for every match after that. Points that would not fit
+/// in a `usize` are reported as 0 rather than overflowing.
+fn get_points_for_matches(number_matches: usize) -> usize {
+    if number_matches == 0 {
+        return 0;
     }
+
+    (1..number_matches)
+        .try_fold(1usize, |points, _| points.checked_mul(2))
+        .unwrap_or(0)
+}
+
+/// Number of cards for each number of matches.
+fn match_histogram(cards: &[ScratchCard]) -> BTreeMap<usize, usize> {
+    cards
+        .iter()
+        .map(ScratchCard::count_matches)
+        .counts()
+        .into_iter()
+        .collect()
 }
 
 fn part1(input: &[String]) -> Result<usize, AocError> {
@@ -107,25 +173,36 @@ fn part1(input: &[String]) -> Result<usize, AocError> {
 fn part2(input: &[String]) -> Result<usize, AocError> {
     let cards: Vec<ScratchCard> = input.iter().map(|line| line.parse()).try_collect()?;
 
-    let mut copies: HashMap<usize, usize> = HashMap::new();
+    count_total_cards(&cards)
+}
 
-    let mut total_cards = 0;
+/// Total number of cards once every card has won copies of the cards following it. Card `n`
+/// with `m` matches wins copies of cards `n + 1` to `n + m`, whatever order the cards are listed
+/// in, and ids missing from the list win nothing. Fails if an id is listed more than once.
+fn count_total_cards(cards: &[ScratchCard]) -> Result<usize, AocError> {
+    let mut matches = BTreeMap::new();
 
     for card in cards {
-        let multiplier = 1 + copies.get(&card.id).copied().unwrap_or_default();
-        total_cards += multiplier;
+        if matches.insert(card.id, card.count_matches()).is_some() {
+            return Err(AocError::DuplicateCard(card.id));
+        }
+    }
+
+    let mut copies: BTreeMap<usize, usize> = matches.keys().map(|&id| (id, 1)).collect();
 
-        let matches = card.count_matches();
+    // Cards only win copies of cards with bigger ids, so going by increasing id every card has won
+    // all of its copies by the time it is reached.
+    for (&id, &number_matches) in &matches {
+        let multiplier = copies[&id];
 
-        for i in 1..=matches {
-            copies
-                .entry(card.id + i)
-                .and_modify(|v| *v += multiplier)
-                .or_insert(multiplier);
+        let last_won_id = id.saturating_add(number_matches);
+
+        for (_, won_copies) in copies.range_mut((Excluded(id), Included(last_won_id))) {
+            *won_copies += multiplier;
         }
     }
 
-    Ok(total_cards)
+    Ok(copies.values().sum())
 }
 
 #[cfg(test)]
@@ -140,13 +217,36 @@ mod tests {
         let scratch_card: ScratchCard = input.parse().unwrap();
         let expected_scratch_card = ScratchCard {
             id: 123,
-            left_numbers: HashSet::from([1, 23]),
-            right_numbers: HashSet::from([4, 56]),
+            left_numbers: vec![1, 23],
+            right_numbers: vec![4, 56],
         };
 
         assert_eq!(scratch_card, expected_scratch_card);
     }
 
+    #[test]
+    fn test_matching_numbers() {
+        let card: ScratchCard = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"
+            .parse()
+            .unwrap();
+
+        assert_eq!(card.matching_numbers(), vec![48, 83, 86, 17]);
+        assert_eq!(card.count_matches(), 4);
+    }
+
+    #[test]
+    fn test_count_matches_duplicates() {
+        let card: ScratchCard = "Card 1: 5 5 7 | 5 8 5 5".parse().unwrap();
+
+        assert_eq!(card.left_numbers, vec![5, 5, 7]);
+        assert_eq!(card.right_numbers, vec![5, 8, 5, 5]);
+        assert_eq!(card.matching_numbers(), vec![5, 5, 5]);
+        assert_eq!(card.count_matches(), 3);
+
+        let card: ScratchCard = "Card 1: 5 5 7 | 5 8".parse().unwrap();
+        assert_eq!(card.count_matches(), 1);
+    }
+
     // Make sure to remove any extra indentation (otherwise it will be part of the string)
     const EXAMPLE: &str = "\
 Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
@@ -157,6 +257,92 @@ Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
 ";
 
+    #[test]
+    fn test_get_points_for_matches() {
+        assert_eq!(get_points_for_matches(0), 0);
+
+        for number_matches in 1..=20 {
+            assert_eq!(
+                get_points_for_matches(number_matches),
+                2usize.pow((number_matches - 1) as u32)
+            );
+        }
+
+        assert_eq!(get_points_for_matches(64), 1 << 63);
+        assert_eq!(get_points_for_matches(65), 0);
+        assert_eq!(get_points_for_matches(1000), 0);
+    }
+
+    #[test]
+    fn test_match_histogram() {
+        let input = to_lines(EXAMPLE);
+        let cards: Vec<ScratchCard> = input.iter().map(|line| line.parse()).try_collect().unwrap();
+
+        assert_eq!(
+            match_histogram(&cards),
+            BTreeMap::from([(0, 2), (1, 1), (2, 2), (4, 1)])
+        );
+    }
+
+    #[test]
+    fn test_count_total_cards_out_of_order() {
+        let input = to_lines(EXAMPLE);
+        let mut cards: Vec<ScratchCard> =
+            input.iter().map(|line| line.parse()).try_collect().unwrap();
+
+        cards.reverse();
+        assert_eq!(count_total_cards(&cards).unwrap(), 30);
+
+        // Without card 3, cards 1 and 2 win one copy fewer each.
+        cards.retain(|card| card.id != 3);
+        assert_eq!(count_total_cards(&cards).unwrap(), 14);
+    }
+
+    #[test]
+    fn test_count_total_cards_by_id() {
+        let input = to_lines(EXAMPLE);
+        let cards: Vec<ScratchCard> = input.iter().map(|line| line.parse()).try_collect().unwrap();
+        let with_ids = |id: fn(usize) -> usize| {
+            cards
+                .iter()
+                .map(|card| ScratchCard {
+                    id: id(card.id),
+                    ..card.clone()
+                })
+                .collect_vec()
+        };
+
+        // Only the differences between ids matter, not where they start.
+        assert_eq!(count_total_cards(&with_ids(|id| id + 9)).unwrap(), 30);
+
+        // Cards 17 to 67 never win cards as far as the next one, so there are no copies.
+        assert_eq!(count_total_cards(&with_ids(|id| id * 10 + 7)).unwrap(), 6);
+
+        // Card 10 wins cards 11 and 12 rather than the next two listed, 11 and 13, and card 11 wins
+        // only the missing card 12, so card 13 is never copied.
+        let input = to_lines("Card 10: 1 2 | 1 2\nCard 11: 3 | 3\nCard 13: 4 | 4\n");
+        assert_eq!(part2(&input).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_count_total_cards_duplicate_id() {
+        let input = to_lines(&format!("{EXAMPLE}Card 2: 1 | 2\n"));
+
+        assert!(matches!(part2(&input), Err(AocError::DuplicateCard(2))));
+    }
+
+    #[test]
+    fn test_count_total_cards_large_id() {
+        let input = to_lines(&format!(
+            "Card {}: 1 2 | 1 2\nCard {}: 3 | 3\n",
+            usize::MAX - 1,
+            usize::MAX
+        ));
+
+        // The first card would win a copy of a card past the biggest id.
+        assert_eq!(part2(&input).unwrap(), 3);
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
@@ -1,41 +1,120 @@
-use std::{io, num::ParseIntError, str::FromStr};
+use std::{env, error, fmt, io, ops::Range, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{pairs, parse_int, read_lines, with_progress, Solution};
 use itertools::Itertools;
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
+pub(crate) enum AocError {
+    Common(aoc::Error),
     InvalidAlmanacMap(String),
     InvalidAlmanac,
+    /// A layer converting `from` something else than what the previous layer converts to
+    /// (`expected`), or than seeds for the first layer.
+    DisconnectedLayer {
+        expected: String,
+        from: String,
+    },
+    /// Maps `a` and `b` of the same `layer` have overlapping source ranges.
+    OverlappingRanges {
+        layer: String,
+        a: AlmanacMap,
+        b: AlmanacMap,
+    },
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::InvalidAlmanacMap(map) => write!(f, "invalid almanac map {map:?}"),
+            AocError::InvalidAlmanac => write!(f, "invalid almanac"),
+            AocError::DisconnectedLayer { expected, from } => {
+                write!(f, "expected a map from {expected}, got one from {from}")
+            }
+            AocError::OverlappingRanges { layer, a, b } => {
+                write!(f, "overlapping {layer} maps \"{a}\" and \"{b}\"")
+            }
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+impl From<aoc::Error> for AocError {
+    fn from(e: aoc::Error) -> Self {
+        Self::Common(e)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day05.txt";
+pub(crate) struct Day05;
+
+impl Solution for Day05 {
+    const INPUT_FILE: &'static str = "day05.txt";
 
+    type Output = usize;
+    type Error = AocError;
+
+    fn part1(input: &[String]) -> Result<usize, AocError> {
+        part1(input)
+    }
+
+    fn part2(input: &[String]) -> Result<usize, AocError> {
+        part2(input)
+    }
+}
+
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
 fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+    if env::args().any(|arg| arg == "--brute-force") {
+        let input = read_lines(aoc::cli_input_path::<Day05>())?;
+        let almanac: Almanac = input.as_slice().try_into()?;
+        let location = almanac
+            .min_location_brute_force()?
+            .ok_or(AocError::InvalidAlmanac)?;
+
+        println!("Part 2 (brute force): {location}");
+    } else {
+        aoc::run::<Day05>()?;
+    }
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    #[cfg(feature = "profile")]
+    eprintln!("Map applications: {}", profile::map_applications());
 
     Ok(())
 }
 
+/// Instrumentation counting how often almanac maps are applied, on every thread.
+#[cfg(feature = "profile")]
+mod profile {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static MAP_APPLICATIONS: AtomicUsize = AtomicUsize::new(0);
+
+    pub fn record_map_application() {
+        MAP_APPLICATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn map_applications() -> usize {
+        MAP_APPLICATIONS.load(Ordering::Relaxed)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct AlmanacMap {
+pub(crate) struct AlmanacMap {
     destination_range_start: usize,
     source_range_start: usize,
     range_length: usize,
@@ -43,12 +122,58 @@ struct AlmanacMap {
 
 impl AlmanacMap {
     fn apply(&self, value: usize) -> Option<usize> {
+        #[cfg(feature = "profile")]
+        profile::record_map_application();
+
         if value < self.source_range_start || value >= self.source_range_start + self.range_length {
             return None;
         }
 
+        // `value` is at least `source_range_start`, so this cannot underflow, even when the
+        // destination is below the source or the numbers don't fit in `i64`.
         Some(value - self.source_range_start + self.destination_range_start)
     }
+
+    /// The inverse of `apply`, from the destination range back to the source range.
+    #[cfg(test)]
+    fn unapply(&self, value: usize) -> Option<usize> {
+        if value < self.destination_range_start
+            || value >= self.destination_range_start + self.range_length
+        {
+            return None;
+        }
+
+        Some(value - self.destination_range_start + self.source_range_start)
+    }
+
+    /// Split `range` into the part covered by this map (translated to the destination) and the
+    /// leftover parts outside of the source range.
+    fn apply_range(&self, range: Range<usize>) -> (Option<Range<usize>>, Vec<Range<usize>>) {
+        #[cfg(feature = "profile")]
+        profile::record_map_application();
+
+        let overlap_start = range.start.max(self.source_range_start);
+        let overlap_end = range.end.min(self.source_range_start + self.range_length);
+
+        if overlap_start >= overlap_end {
+            return (None, vec![range]);
+        }
+
+        let mut leftovers = vec![];
+
+        if range.start < overlap_start {
+            leftovers.push(range.start..overlap_start);
+        }
+
+        if overlap_end < range.end {
+            leftovers.push(overlap_end..range.end);
+        }
+
+        let mapped = (overlap_start - self.source_range_start + self.destination_range_start)
+            ..(overlap_end - self.source_range_start + self.destination_range_start);
+
+        (Some(mapped), leftovers)
+    }
 }
 
 fn apply_all(maps: &[AlmanacMap], value: usize) -> usize {
@@ -58,6 +183,102 @@ fn apply_all(maps: &[AlmanacMap], value: usize) -> usize {
         .unwrap_or(value)
 }
 
+/// The inverse of `apply_all`, assuming no two maps share part of their destination range.
+#[cfg(test)]
+fn unapply_all(maps: &[AlmanacMap], value: usize) -> usize {
+    maps.iter()
+        .filter_map(|map| map.unapply(value))
+        .next()
+        .unwrap_or(value)
+}
+
+fn apply_all_ranges(maps: &[AlmanacMap], ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let mut mapped = vec![];
+    let mut unmapped = ranges;
+
+    for map in maps {
+        let mut leftovers = vec![];
+
+        for range in unmapped {
+            let (range_mapped, range_leftovers) = map.apply_range(range);
+
+            mapped.extend(range_mapped);
+            leftovers.extend(range_leftovers);
+        }
+
+        unmapped = leftovers;
+    }
+
+    mapped.extend(unmapped);
+
+    mapped
+}
+
+/// Compose `segment` with the layer `maps`, splitting it wherever its destination range crosses
+/// the edge of a map. The pieces still map from the source range of `segment`.
+fn compose_layer(segment: AlmanacMap, maps: &[AlmanacMap]) -> Vec<AlmanacMap> {
+    let piece = |values: Range<usize>, destination_range_start: usize| AlmanacMap {
+        destination_range_start,
+        source_range_start: segment.source_range_start + values.start
+            - segment.destination_range_start,
+        range_length: values.len(),
+    };
+
+    let values =
+        segment.destination_range_start..segment.destination_range_start + segment.range_length;
+
+    let mut composed = vec![];
+    let mut unmapped = vec![values];
+
+    for map in maps {
+        let mut leftovers = vec![];
+
+        for values in unmapped {
+            let (mapped, range_leftovers) = map.apply_range(values);
+
+            if let Some(mapped) = mapped {
+                let values_start =
+                    mapped.start - map.destination_range_start + map.source_range_start;
+
+                composed.push(piece(
+                    values_start..values_start + mapped.len(),
+                    mapped.start,
+                ));
+            }
+            leftovers.extend(range_leftovers);
+        }
+
+        unmapped = leftovers;
+    }
+
+    composed.extend(
+        unmapped
+            .into_iter()
+            .map(|values| piece(values.clone(), values.start)),
+    );
+    composed.retain(|piece| piece.range_length > 0);
+
+    composed
+}
+
+/// Sort `ranges` and merge those that overlap or touch, dropping empty ones.
+#[cfg(test)]
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = vec![];
+
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+}
+
 impl FromStr for AlmanacMap {
     type Err = AocError;
 
@@ -68,49 +289,221 @@ impl FromStr for AlmanacMap {
             .ok_or_else(|| AocError::InvalidAlmanacMap(s.to_owned()))?;
 
         Ok(Self {
-            destination_range_start: destination_range_start.parse()?,
-            source_range_start: source_range_start.parse()?,
-            range_length: range_length.parse()?,
+            destination_range_start: parse_int(destination_range_start)?,
+            source_range_start: parse_int(source_range_start)?,
+            range_length: parse_int(range_length)?,
         })
     }
 }
 
+/// Renders the map as it is listed in the input, e.g. `50 98 2`.
+impl fmt::Display for AlmanacMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.destination_range_start, self.source_range_start, self.range_length
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Almanac {
+pub(crate) struct Almanac {
     seeds: Vec<usize>,
-    seed_to_soil_maps: Vec<AlmanacMap>,
-    soil_to_fertilizer_maps: Vec<AlmanacMap>,
-    fertilizer_to_water_maps: Vec<AlmanacMap>,
-    water_to_light_maps: Vec<AlmanacMap>,
-    light_to_temperature_maps: Vec<AlmanacMap>,
-    temperature_to_humidity_maps: Vec<AlmanacMap>,
-    humidity_to_location_maps: Vec<AlmanacMap>,
+    /// `(from, to, maps)` for each `from-to-to map:` section, in the order of the input.
+    stages: Vec<(String, String, Vec<AlmanacMap>)>,
 }
 
 impl Almanac {
-    fn convert_seed(&self, seed: usize) -> usize {
-        let soil = apply_all(&self.seed_to_soil_maps, seed);
-        let fertilizer = apply_all(&self.soil_to_fertilizer_maps, soil);
-        let water = apply_all(&self.fertilizer_to_water_maps, fertilizer);
-        let light = apply_all(&self.water_to_light_maps, water);
-        let temperature = apply_all(&self.light_to_temperature_maps, light);
-        let humidity = apply_all(&self.temperature_to_humidity_maps, temperature);
+    /// Check that the layers convert seeds step by step, each from what the previous one converts
+    /// to, and that no two maps of a layer cover the same source values, which would make
+    /// `apply_all` depend on the order of the maps.
+    fn validate(&self) -> Result<(), AocError> {
+        let mut expected = "seed";
+
+        for (from, to, _) in &self.stages {
+            if from != expected {
+                return Err(AocError::DisconnectedLayer {
+                    expected: expected.to_owned(),
+                    from: from.clone(),
+                });
+            }
+
+            expected = to;
+        }
+
+        for (from, to, maps) in &self.stages {
+            let sorted_maps = maps.iter().sorted_by_key(|map| map.source_range_start);
+
+            for (a, b) in sorted_maps.tuple_windows() {
+                if b.source_range_start < a.source_range_start + a.range_length {
+                    return Err(AocError::OverlappingRanges {
+                        layer: format!("{from}-to-{to}"),
+                        a: *a,
+                        b: *b,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like `==`, but ignoring the order of the maps within each layer, which doesn't change
+    /// anything once the almanac is validated.
+    #[cfg(test)]
+    fn semantically_eq(&self, other: &Almanac) -> bool {
+        fn sorted_stages(almanac: &Almanac) -> Vec<(&String, &String, Vec<&AlmanacMap>)> {
+            almanac
+                .stages
+                .iter()
+                .map(|(from, to, maps)| {
+                    let maps = maps.iter().sorted_by_key(|map| {
+                        (
+                            map.source_range_start,
+                            map.destination_range_start,
+                            map.range_length,
+                        )
+                    });
+
+                    (from, to, maps.collect_vec())
+                })
+                .collect()
+        }
+
+        self.seeds == other.seeds && sorted_stages(self) == sorted_stages(other)
+    }
+
+    fn layers(&self) -> impl Iterator<Item = &[AlmanacMap]> {
+        self.stages.iter().map(|(_, _, maps)| maps.as_slice())
+    }
 
-        apply_all(&self.humidity_to_location_maps, humidity)
+    pub(crate) fn convert_seed(&self, seed: usize) -> usize {
+        self.layers()
+            .fold(seed, |value, maps| apply_all(maps, value))
+    }
+
+    /// The seed that `convert_seed` takes to `location`, going through the layers backwards.
+    #[cfg(test)]
+    fn convert_location_to_seed(&self, location: usize) -> usize {
+        self.stages
+            .iter()
+            .rev()
+            .fold(location, |value, (_, _, maps)| unapply_all(maps, value))
+    }
+
+    /// Compose all the layers into a single one, so that `apply_all` on it converts a seed straight
+    /// to its location. Seeds that no layer moves are left out, like in any other layer.
+    fn compile(&self) -> Vec<AlmanacMap> {
+        let identity = AlmanacMap {
+            destination_range_start: 0,
+            source_range_start: 0,
+            range_length: usize::MAX,
+        };
+
+        let mut compiled = self.layers().fold(vec![identity], |segments, maps| {
+            segments
+                .into_iter()
+                .flat_map(|segment| compose_layer(segment, maps))
+                .collect()
+        });
+        compiled.retain(|map| map.source_range_start != map.destination_range_start);
+
+        compiled
+    }
+
+    fn convert_seed_compiled(&self, compiled: &[AlmanacMap], seed: usize) -> usize {
+        apply_all(compiled, seed)
+    }
+
+    /// Convert a batch of seeds one layer at a time, rather than one seed at a time.
+    #[cfg(test)]
+    pub(crate) fn convert_seeds_batched(&self, seeds: &[usize]) -> Vec<usize> {
+        let mut values = seeds.to_vec();
+
+        for maps in self.layers() {
+            for value in values.iter_mut() {
+                *value = apply_all(maps, *value);
+            }
+        }
+
+        values
     }
 
     fn convert_all_seeds(&self) -> impl Iterator<Item = usize> + '_ {
-        self.seeds.iter().map(|&seed| self.convert_seed(seed))
+        let compiled = self.compile();
+
+        self.seeds
+            .iter()
+            .map(move |&seed| self.convert_seed_compiled(&compiled, seed))
+    }
+
+    fn seed_ranges(&self) -> Result<Vec<Range<usize>>, AocError> {
+        let seed_pairs = pairs(&self.seeds)?;
+
+        Ok(seed_pairs
+            .into_iter()
+            .map(|(start, length)| start..start + length)
+            .collect())
     }
 
-    fn convert_all_seeds_2(&self) -> impl Iterator<Item = usize> + '_ {
-        let all_seeds = self
-            .seeds
+    /// How many seeds converting every seed of the part 2 ranges goes through, summed in `u128`
+    /// since it can be well beyond what is practical. A trailing start without a length counts
+    /// for nothing.
+    #[cfg(test)]
+    fn estimated_part2_seeds(&self) -> u128 {
+        self.seeds
             .iter()
-            .tuples()
-            .flat_map(|(&start, &length)| start..start + length);
+            .skip(1)
+            .step_by(2)
+            .map(|&length| length as u128)
+            .sum()
+    }
+
+    /// Convert every seed of the part 2 ranges one by one. Much slower than converting the ranges
+    /// themselves, but handy to check against.
+    #[cfg(test)]
+    fn convert_all_seeds_2(&self) -> Result<impl Iterator<Item = usize> + '_, AocError> {
+        let all_seeds = self.seed_ranges()?.into_iter().flatten();
 
-        all_seeds.map(|seed| self.convert_seed(seed))
+        Ok(all_seeds.map(|seed| self.convert_seed(seed)))
+    }
+
+    /// The lowest location of the part 2 seeds, converting them one by one (on every core with
+    /// the `parallel` feature) and reporting progress. Takes far longer than `part2` on real
+    /// inputs, so it is only run with `--brute-force`, to check `part2` against.
+    fn min_location_brute_force(&self) -> Result<Option<usize>, AocError> {
+        let seed_ranges = self.seed_ranges()?;
+        let total_seeds = seed_ranges.iter().map(ExactSizeIterator::len).sum();
+        let seeds = with_progress(total_seeds, seed_ranges.into_iter().flatten());
+
+        #[cfg(not(feature = "parallel"))]
+        let location = seeds.map(|seed| self.convert_seed(seed)).min();
+        #[cfg(feature = "parallel")]
+        let location = {
+            use rayon::prelude::*;
+
+            seeds.par_bridge().map(|seed| self.convert_seed(seed)).min()
+        };
+
+        Ok(location)
+    }
+
+    fn convert_seed_ranges(&self, seed_ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+        self.layers()
+            .fold(seed_ranges, |ranges, maps| apply_all_ranges(maps, ranges))
+    }
+
+    /// All locations reachable from the part 2 seed ranges, as sorted and merged half-open
+    /// `(start, end)` intervals.
+    #[cfg(test)]
+    fn location_ranges_for_seed_ranges(&self) -> Result<Vec<(usize, usize)>, AocError> {
+        let location_ranges = self.convert_seed_ranges(self.seed_ranges()?);
+
+        Ok(merge_ranges(location_ranges)
+            .into_iter()
+            .map(|range| (range.start, range.end))
+            .collect())
     }
 }
 
@@ -118,56 +511,47 @@ impl TryFrom<&[String]> for Almanac {
     type Error = AocError;
 
     fn try_from(value: &[String]) -> Result<Self, Self::Error> {
-        let mut lines = value.iter();
+        // Blank lines after the last map are not a layer.
+        let end = value
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |i| i + 1);
+        let mut lines = value[..end].iter();
 
         let seeds = lines
             .next()
             .and_then(|s| s.strip_prefix("seeds: "))
             .ok_or(AocError::InvalidAlmanac)?;
-        let seeds = seeds.split(' ').map(|seed| seed.parse()).try_collect()?;
+        let seeds = seeds.split(' ').map(parse_int).try_collect()?;
 
         if !lines.next().is_some_and(String::is_empty) {
             return Err(AocError::InvalidAlmanac);
         }
 
-        fn parse_maps<'a>(
-            header: &str,
-            lines: &mut impl Iterator<Item = &'a String>,
-        ) -> Result<Vec<AlmanacMap>, AocError> {
-            if !lines.next().is_some_and(|s| s == header) {
-                return Err(AocError::InvalidAlmanac);
-            }
+        let mut stages = vec![];
+
+        while let Some(header) = lines.next() {
+            let (from, to) = header
+                .strip_suffix(" map:")
+                .and_then(|names| names.split_once("-to-"))
+                .ok_or(AocError::InvalidAlmanac)?;
 
             let mut maps = vec![];
 
-            for line in lines {
+            for line in lines.by_ref() {
                 if line.is_empty() {
                     break;
                 }
                 maps.push(line.parse()?);
             }
 
-            Ok(maps)
+            stages.push((from.to_owned(), to.to_owned(), maps));
         }
 
-        let seed_to_soil_maps = parse_maps("seed-to-soil map:", &mut lines)?;
-        let soil_to_fertilizer_maps = parse_maps("soil-to-fertilizer map:", &mut lines)?;
-        let fertilizer_to_water_maps = parse_maps("fertilizer-to-water map:", &mut lines)?;
-        let water_to_light_maps = parse_maps("water-to-light map:", &mut lines)?;
-        let light_to_temperature_maps = parse_maps("light-to-temperature map:", &mut lines)?;
-        let temperature_to_humidity_maps = parse_maps("temperature-to-humidity map:", &mut lines)?;
-        let humidity_to_location_maps = parse_maps("humidity-to-location map:", &mut lines)?;
+        let almanac = Self { seeds, stages };
+        almanac.validate()?;
 
-        Ok(Self {
-            seeds,
-            seed_to_soil_maps,
-            soil_to_fertilizer_maps,
-            fertilizer_to_water_maps,
-            water_to_light_maps,
-            light_to_temperature_maps,
-            temperature_to_humidity_maps,
-            humidity_to_location_maps,
-        })
+        Ok(almanac)
     }
 }
 
@@ -180,11 +564,16 @@ fn part1(input: &[String]) -> Result<usize, AocError> {
         .ok_or(AocError::InvalidAlmanac)
 }
 
+/// Convert the seed ranges through every layer, and take the lowest location reached.
 fn part2(input: &[String]) -> Result<usize, AocError> {
     let almanac: Almanac = input.try_into()?;
 
-    almanac
-        .convert_all_seeds_2()
+    let location_ranges = almanac.convert_seed_ranges(almanac.seed_ranges()?);
+
+    location_ranges
+        .iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| range.start)
         .min()
         .ok_or(AocError::InvalidAlmanac)
 }
@@ -194,6 +583,7 @@ mod tests {
     use super::*;
 
     use aoc::to_lines;
+    use proptest::prelude::*;
 
     #[test]
     fn test_parse_almanac() {
@@ -203,7 +593,7 @@ seeds: 1 2 3
 
 seed-to-soil map:
 3 4 5
-5 6 7
+5 9 7
 
 soil-to-fertilizer map:
 7 8 9
@@ -227,53 +617,175 @@ humidity-to-location map:
         let almanac: Almanac = input.as_slice().try_into().unwrap();
         let expected_almanac = Almanac {
             seeds: vec![1, 2, 3],
-            seed_to_soil_maps: vec![
-                AlmanacMap {
-                    destination_range_start: 3,
-                    source_range_start: 4,
-                    range_length: 5,
-                },
-                AlmanacMap {
-                    destination_range_start: 5,
-                    source_range_start: 6,
-                    range_length: 7,
-                },
+            stages: vec![
+                (
+                    "seed".to_owned(),
+                    "soil".to_owned(),
+                    vec![
+                        AlmanacMap {
+                            destination_range_start: 3,
+                            source_range_start: 4,
+                            range_length: 5,
+                        },
+                        AlmanacMap {
+                            destination_range_start: 5,
+                            source_range_start: 9,
+                            range_length: 7,
+                        },
+                    ],
+                ),
+                (
+                    "soil".to_owned(),
+                    "fertilizer".to_owned(),
+                    vec![AlmanacMap {
+                        destination_range_start: 7,
+                        source_range_start: 8,
+                        range_length: 9,
+                    }],
+                ),
+                (
+                    "fertilizer".to_owned(),
+                    "water".to_owned(),
+                    vec![AlmanacMap {
+                        destination_range_start: 9,
+                        source_range_start: 0,
+                        range_length: 1,
+                    }],
+                ),
+                (
+                    "water".to_owned(),
+                    "light".to_owned(),
+                    vec![AlmanacMap {
+                        destination_range_start: 1,
+                        source_range_start: 2,
+                        range_length: 3,
+                    }],
+                ),
+                (
+                    "light".to_owned(),
+                    "temperature".to_owned(),
+                    vec![AlmanacMap {
+                        destination_range_start: 3,
+                        source_range_start: 4,
+                        range_length: 5,
+                    }],
+                ),
+                (
+                    "temperature".to_owned(),
+                    "humidity".to_owned(),
+                    vec![AlmanacMap {
+                        destination_range_start: 5,
+                        source_range_start: 6,
+                        range_length: 7,
+                    }],
+                ),
+                (
+                    "humidity".to_owned(),
+                    "location".to_owned(),
+                    vec![AlmanacMap {
+                        destination_range_start: 7,
+                        source_range_start: 8,
+                        range_length: 9,
+                    }],
+                ),
             ],
-            soil_to_fertilizer_maps: vec![AlmanacMap {
-                destination_range_start: 7,
-                source_range_start: 8,
-                range_length: 9,
-            }],
-            fertilizer_to_water_maps: vec![AlmanacMap {
-                destination_range_start: 9,
-                source_range_start: 0,
-                range_length: 1,
-            }],
-            water_to_light_maps: vec![AlmanacMap {
-                destination_range_start: 1,
-                source_range_start: 2,
-                range_length: 3,
-            }],
-            light_to_temperature_maps: vec![AlmanacMap {
-                destination_range_start: 3,
-                source_range_start: 4,
-                range_length: 5,
-            }],
-            temperature_to_humidity_maps: vec![AlmanacMap {
-                destination_range_start: 5,
-                source_range_start: 6,
-                range_length: 7,
-            }],
-            humidity_to_location_maps: vec![AlmanacMap {
-                destination_range_start: 7,
-                source_range_start: 8,
-                range_length: 9,
-            }],
         };
 
         assert_eq!(almanac, expected_almanac);
     }
 
+    #[test]
+    fn test_parse_almanac_custom_layers() {
+        let input = to_lines(
+            "\
+seeds: 1 20
+
+seed-to-colour map:
+10 0 5
+
+colour-to-planet map:
+100 10 2
+",
+        );
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        let names = almanac
+            .stages
+            .iter()
+            .map(|(from, to, _)| (from.as_str(), to.as_str()))
+            .collect_vec();
+        assert_eq!(names, vec![("seed", "colour"), ("colour", "planet")]);
+
+        assert_eq!(almanac.convert_seed(1), 101);
+        assert_eq!(almanac.convert_seed(4), 14);
+        assert_eq!(almanac.convert_seed(20), 20);
+
+        let input = to_lines("seeds: 1\n\nseed to colour map:\n10 0 5\n");
+        let result: Result<Almanac, _> = input.as_slice().try_into();
+        assert!(matches!(result, Err(AocError::InvalidAlmanac)));
+    }
+
+    #[test]
+    fn test_parse_almanac_disconnected_layers() {
+        let parse = |input: &str| -> Result<Almanac, _> { to_lines(input).as_slice().try_into() };
+
+        let result = parse("seeds: 1\n\ncolour-to-planet map:\n100 10 2\n");
+        assert!(matches!(
+            result,
+            Err(AocError::DisconnectedLayer { expected, from }) if expected == "seed" && from == "colour"
+        ));
+
+        let result =
+            parse("seeds: 1\n\nseed-to-soil map:\n10 0 5\n\nwater-to-light map:\n100 10 2\n");
+        let Err(error) = result else {
+            panic!("expected a disconnected layer");
+        };
+        assert_eq!(
+            error.to_string(),
+            "expected a map from soil, got one from water"
+        );
+    }
+
+    #[test]
+    fn test_parse_almanac_trailing_blank_lines() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        let padded = to_lines(&format!("{EXAMPLE}\n\n"));
+        assert_eq!(padded.last().map(String::as_str), Some(""));
+        assert_eq!(Almanac::try_from(padded.as_slice()).unwrap(), almanac);
+    }
+
+    #[test]
+    fn test_parse_almanac_overlapping_ranges() {
+        let input = to_lines(
+            "\
+seeds: 1
+
+seed-to-soil map:
+0 10 5
+
+soil-to-fertilizer map:
+50 20 10
+0 0 5
+70 25 3
+",
+        );
+        let result: Result<Almanac, _> = input.as_slice().try_into();
+
+        let Err(AocError::OverlappingRanges { layer, a, b }) = result else {
+            panic!("expected overlapping ranges, got {result:?}");
+        };
+        assert_eq!(layer, "soil-to-fertilizer");
+        assert_eq!(a.source_range_start, 20);
+        assert_eq!(b.source_range_start, 25);
+
+        // Ranges that only touch don't overlap.
+        let input = to_lines("seeds: 1\n\nseed-to-soil map:\n0 10 5\n50 15 5\n");
+        let result: Result<Almanac, _> = input.as_slice().try_into();
+        assert!(result.is_ok());
+    }
+
     #[test]
     fn test_almanac_map_apply() {
         let map = AlmanacMap {
@@ -286,6 +798,71 @@ humidity-to-location map:
         assert_eq!(map.apply(98), Some(50));
         assert_eq!(map.apply(99), Some(51));
         assert_eq!(map.apply(100), None);
+
+        // Beyond `i64`.
+        let big = 1 << 63;
+        let map: AlmanacMap = format!("0 {big} 1").parse().unwrap();
+        assert_eq!(map.apply(big), Some(0));
+    }
+
+    #[cfg(feature = "profile")]
+    #[test]
+    fn test_profile_map_applications() {
+        let maps = vec![
+            AlmanacMap {
+                destination_range_start: 50,
+                source_range_start: 98,
+                range_length: 2,
+            },
+            AlmanacMap {
+                destination_range_start: 52,
+                source_range_start: 50,
+                range_length: 48,
+            },
+        ];
+
+        // Other tests apply maps at the same time, so only a lower bound of the count is known.
+        let before = profile::map_applications();
+
+        // Matches the first map, so the second one is never tried.
+        apply_all(&maps, 98);
+        assert!(profile::map_applications() > before);
+
+        // Falls through both maps.
+        apply_all(&maps, 13);
+        assert!(profile::map_applications() >= before + 3);
+
+        // The range is split by the first map, and both leftovers go through the second one.
+        let range = 90..110;
+        apply_all_ranges(&maps, vec![range]);
+        assert!(profile::map_applications() >= before + 6);
+    }
+
+    #[cfg(all(feature = "profile", feature = "parallel"))]
+    #[test]
+    fn test_profile_map_applications_parallel() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+        let before = profile::map_applications();
+
+        almanac.min_location_brute_force().unwrap();
+
+        // Every one of the 27 seeds goes through each of the 7 layers, on rayon's threads.
+        assert!(profile::map_applications() >= before + 27 * 7);
+    }
+
+    #[test]
+    fn test_almanac_map_unapply() {
+        let map = AlmanacMap {
+            destination_range_start: 50,
+            source_range_start: 98,
+            range_length: 2,
+        };
+
+        assert_eq!(map.unapply(49), None);
+        assert_eq!(map.unapply(50), Some(98));
+        assert_eq!(map.unapply(51), Some(99));
+        assert_eq!(map.unapply(52), None);
     }
 
     #[test]
@@ -309,6 +886,35 @@ humidity-to-location map:
         assert_eq!(apply_all(&maps, 13), 13);
     }
 
+    #[test]
+    fn test_almanac_map_apply_range() {
+        let map = AlmanacMap {
+            destination_range_start: 50,
+            source_range_start: 98,
+            range_length: 2,
+        };
+
+        // Outside of the map, the range is left whole.
+        let range = 0..10;
+        assert_eq!(map.apply_range(range.clone()), (None, vec![range]));
+        assert_eq!(map.apply_range(98..100), (Some(50..52), vec![]));
+
+        let below = 90..98;
+        assert_eq!(map.apply_range(90..99), (Some(50..51), vec![below]));
+        assert_eq!(
+            map.apply_range(90..110),
+            (Some(50..52), vec![90..98, 100..110])
+        );
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        assert_eq!(
+            merge_ranges(vec![10..12, 0..3, 3..5, 11..15, 20..20]),
+            vec![0..5, 10..15]
+        );
+    }
+
     // Make sure to remove any extra indentation (otherwise it will be part of the string)
     const EXAMPLE: &str = "\
 seeds: 79 14 55 13
@@ -346,6 +952,183 @@ humidity-to-location map:
 56 93 4
 ";
 
+    #[test]
+    fn test_seed_ranges() {
+        let input = to_lines(EXAMPLE);
+        let mut almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        assert_eq!(almanac.seed_ranges().unwrap(), vec![79..93, 55..68]);
+
+        almanac.seeds.pop();
+        assert!(matches!(
+            almanac.seed_ranges(),
+            Err(AocError::Common(aoc::Error::OddLength(3)))
+        ));
+    }
+
+    #[test]
+    fn test_estimated_part2_seeds() {
+        let input = to_lines(EXAMPLE);
+        let mut almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        assert_eq!(almanac.estimated_part2_seeds(), 27);
+
+        almanac.seeds = vec![0, usize::MAX, 1, usize::MAX];
+        assert_eq!(almanac.estimated_part2_seeds(), 2 * usize::MAX as u128);
+    }
+
+    #[test]
+    fn test_convert_seeds_batched() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        let seeds = (0..100).collect_vec();
+        let expected_locations = seeds
+            .iter()
+            .map(|&seed| almanac.convert_seed(seed))
+            .collect_vec();
+
+        assert_eq!(almanac.convert_seeds_batched(&seeds), expected_locations);
+        assert_eq!(
+            almanac.convert_seeds_batched(&almanac.seeds),
+            vec![82, 43, 86, 35]
+        );
+    }
+
+    #[test]
+    fn test_almanac_semantically_eq() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        let mut reordered = almanac.clone();
+        for (_, _, maps) in &mut reordered.stages {
+            maps.reverse();
+        }
+
+        assert_ne!(reordered, almanac);
+        assert!(reordered.semantically_eq(&almanac));
+        assert!(almanac.semantically_eq(&reordered));
+
+        let mut changed = reordered.clone();
+        changed.stages[0].2[0].range_length += 1;
+        assert!(!changed.semantically_eq(&almanac));
+
+        let mut changed = reordered;
+        changed.seeds.reverse();
+        assert!(!changed.semantically_eq(&almanac));
+    }
+
+    #[test]
+    fn test_convert_location_to_seed() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        assert_eq!(almanac.convert_location_to_seed(35), 13);
+        assert_eq!(almanac.convert_location_to_seed(46), 82);
+
+        for seed in almanac.seeds.iter().copied().chain(0..100) {
+            let location = almanac.convert_seed(seed);
+
+            assert_eq!(almanac.convert_location_to_seed(location), seed);
+        }
+    }
+
+    #[test]
+    fn test_convert_seed_compiled() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+        let compiled = almanac.compile();
+
+        for &seed in &almanac.seeds {
+            assert_eq!(
+                almanac.convert_seed_compiled(&compiled, seed),
+                almanac.convert_seed(seed)
+            );
+        }
+
+        proptest!(|(seed in 0..200usize)| {
+            prop_assert_eq!(
+                almanac.convert_seed_compiled(&compiled, seed),
+                almanac.convert_seed(seed)
+            );
+        });
+        proptest!(|(seed in any::<usize>())| {
+            prop_assert_eq!(
+                almanac.convert_seed_compiled(&compiled, seed),
+                almanac.convert_seed(seed)
+            );
+        });
+    }
+
+    #[test]
+    fn test_location_ranges_for_seed_ranges() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        assert_eq!(
+            almanac.location_ranges_for_seed_ranges().unwrap(),
+            vec![(46, 61), (82, 85), (86, 90), (94, 99)]
+        );
+    }
+
+    #[test]
+    fn test_location_ranges_split_and_merge() {
+        let input = to_lines(
+            "\
+seeds: 10 10
+
+seed-to-soil map:
+100 14 2
+
+soil-to-fertilizer map:
+14 100 2
+
+fertilizer-to-water map:
+
+water-to-light map:
+
+light-to-temperature map:
+
+temperature-to-humidity map:
+
+humidity-to-location map:
+",
+        );
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        let soil = apply_all_ranges(&almanac.stages[0].2, almanac.seed_ranges().unwrap());
+        assert_eq!(merge_ranges(soil), vec![10..14, 16..20, 100..102]);
+
+        assert_eq!(
+            almanac.location_ranges_for_seed_ranges().unwrap(),
+            vec![(10, 20)]
+        );
+    }
+
+    #[test]
+    fn test_convert_seed_ranges_matches_brute_force() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        let location_ranges = almanac.convert_seed_ranges(almanac.seed_ranges().unwrap());
+        let mut locations = location_ranges.into_iter().flatten().collect_vec();
+        locations.sort_unstable();
+
+        let mut expected_locations = almanac.convert_all_seeds_2().unwrap().collect_vec();
+        expected_locations.sort_unstable();
+
+        assert_eq!(locations, expected_locations);
+        assert_eq!(locations.first(), Some(&46));
+    }
+
+    #[test]
+    fn test_min_location_brute_force() {
+        let input = to_lines(EXAMPLE);
+        let almanac: Almanac = input.as_slice().try_into().unwrap();
+
+        assert_eq!(almanac.min_location_brute_force().unwrap(), Some(46));
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
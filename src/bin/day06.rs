@@ -1,41 +1,82 @@
-use std::{io, iter::zip, num::ParseIntError};
+use std::{collections::HashMap, error, fmt, io, iter::zip, ops::RangeInclusive};
 
-use aoc::read_lines;
+use aoc::{parse_int, Solution};
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
+pub(crate) enum AocError {
+    Common(aoc::Error),
     InvalidRaces,
+    /// The input should be exactly a `Time:` and a `Distance:` line, `extra` holds any lines after
+    /// those.
+    InvalidLineCount {
+        count: usize,
+        extra: Vec<String>,
+    },
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::InvalidRaces => write!(f, "invalid races"),
+            AocError::InvalidLineCount { count, extra } if extra.is_empty() => {
+                write!(f, "expected 2 lines, got {count}")
+            }
+            AocError::InvalidLineCount { count, extra } => {
+                write!(f, "expected 2 lines, got {count}; extra lines: {extra:?}")
+            }
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+impl From<aoc::Error> for AocError {
+    fn from(e: aoc::Error) -> Self {
+        Self::Common(e)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day06.txt";
+pub(crate) struct Day06;
 
-fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+impl Solution for Day06 {
+    const INPUT_FILE: &'static str = "day06.txt";
+
+    type Output = usize;
+    type Error = AocError;
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    fn part1(input: &[String]) -> Result<usize, AocError> {
+        part1(input)
+    }
 
-    Ok(())
+    fn part2(input: &[String]) -> Result<usize, AocError> {
+        part2(input)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
+fn main() -> Result<(), AocError> {
+    aoc::run::<Day06>()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Race {
     time_allowed: usize,
     distance_record: usize,
@@ -50,88 +91,227 @@ impl Race {
     }
 
     fn get_number_of_ways_to_win(&self) -> usize {
-        (1..self.time_allowed)
-            .map(|time_held| self.get_distance_for_time_holding_button(time_held))
-            .filter(|distance| distance > &self.distance_record)
-            .count()
+        self.winning_hold_range()
+            .map_or(0, |range| range.end() - range.start() + 1)
+    }
+
+    /// Like `get_number_of_ways_to_win`, but also counting hold times that exactly tie the record.
+    #[cfg(test)]
+    fn get_number_of_ways_to_beat_or_tie(&self) -> usize {
+        match self.distance_record.checked_sub(1) {
+            // Distances are integers, so tying the record is beating the one just below it.
+            Some(distance_record) => Race {
+                distance_record,
+                ..*self
+            }
+            .get_number_of_ways_to_win(),
+            None => self.time_allowed.saturating_sub(1),
+        }
+    }
+
+    /// The first and last hold times that beat the record, if any does.
+    ///
+    /// Holding the button for `t` wins when `t * (T - t) > D`, i.e. strictly between the roots of
+    /// `t^2 - T*t + D = 0`.
+    fn winning_hold_range(&self) -> Option<RangeInclusive<usize>> {
+        let time = self.time_allowed as f64;
+        let record = self.distance_record as f64;
+
+        let discriminant = time * time - 4.0 * record;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        // Nudge the roots inwards, so that a hold time exactly matching the record doesn't count.
+        let root = discriminant.sqrt();
+        let mut lowest = ((time - root) / 2.0 + 1e-9).ceil().max(1.0) as usize;
+        let mut highest = ((time + root) / 2.0 - 1e-9).floor() as usize;
+
+        // Large races lose precision in `f64`, so settle the boundaries with exact distances.
+        let wins =
+            |time_held| self.get_distance_for_time_holding_button(time_held) > self.distance_record;
+
+        while lowest <= highest && !wins(lowest) {
+            lowest += 1;
+        }
+        while lowest > 1 && wins(lowest - 1) {
+            lowest -= 1;
+        }
+        while highest >= lowest && !wins(highest) {
+            highest -= 1;
+        }
+        while highest + 1 < self.time_allowed && wins(highest + 1) {
+            highest += 1;
+        }
+
+        (lowest <= highest).then_some(lowest..=highest)
+    }
+
+    /// Like `get_number_of_ways_to_win`, but exact for any race, even when the distances
+    /// involved don't fit in a `usize`.
+    ///
+    /// Distances are symmetric around half of the time allowed, so only the first winning hold
+    /// time is needed. The `f64` root is only a first guess: it is bracketed with exact distances,
+    /// then narrowed down by bisection.
+    #[cfg(test)]
+    fn ways_to_win_exact(&self) -> usize {
+        let time = self.time_allowed as u128;
+        let record = self.distance_record as u128;
+        let wins = |time_held: u128| time_held * (time - time_held) > record;
+
+        let half = time / 2;
+        if !wins(half) {
+            return 0;
+        }
+
+        // The smaller root of `t^2 - T*t + D = 0`, in a form that doesn't cancel out for big `T`.
+        let discriminant = (time as f64).powi(2) - 4.0 * record as f64;
+        let estimate = 2.0 * record as f64 / (time as f64 + discriminant.max(0.0).sqrt());
+
+        // Holding for 0 never wins, and holding for `half` does.
+        let mut winning = (estimate as u128).clamp(1, half);
+        let mut step = 1;
+        while !wins(winning) {
+            winning = (winning + step).min(half);
+            step *= 2;
+        }
+
+        let mut losing = winning;
+        let mut step = 1;
+        while wins(losing) {
+            losing = losing.saturating_sub(step);
+            step *= 2;
+        }
+
+        while winning - losing > 1 {
+            let middle = losing + (winning - losing) / 2;
+
+            if wins(middle) {
+                winning = middle;
+            } else {
+                losing = middle;
+            }
+        }
+
+        (time - 2 * winning + 1) as usize
+    }
+
+    /// The furthest distance achievable, by holding the button for half of the time allowed
+    /// (either half, when the time is odd).
+    #[cfg(test)]
+    fn best_distance(&self) -> usize {
+        let time_held = self.time_allowed / 2;
+
+        self.get_distance_for_time_holding_button(time_held)
+    }
+
+    /// How far the best distance is over the record (negative when the race can't be won). Any
+    /// difference of two distances fits in an `i128`.
+    #[cfg(test)]
+    fn margin_over_record(&self) -> i128 {
+        self.best_distance() as i128 - self.distance_record as i128
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Races(Vec<Race>);
 
-impl TryFrom<&[String]> for Races {
-    type Error = AocError;
-
-    fn try_from(value: &[String]) -> Result<Self, Self::Error> {
-        let [times, distances] = value else {
-            return Err(AocError::InvalidRaces);
-        };
-
-        static WHITESPACE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
-
-        let times = times
-            .strip_prefix("Time:")
-            .map(|t| t.trim())
-            .ok_or(AocError::InvalidRaces)?;
-        let times: Vec<usize> = WHITESPACE_REGEX
-            .split(times)
-            .map(|time| time.parse())
-            .try_collect()?;
-
-        let distances = distances
-            .strip_prefix("Distance:")
-            .map(|d| d.trim())
-            .ok_or(AocError::InvalidRaces)?;
-        let distances: Vec<usize> = WHITESPACE_REGEX
-            .split(distances)
-            .map(|distance| distance.parse())
-            .try_collect()?;
-
-        let races = zip(times, distances)
-            .map(|(time, distance)| Race {
-                time_allowed: time,
-                distance_record: distance,
+impl Races {
+    /// The product of the number of ways to win each race, computing identical races only once.
+    fn total_ways(&self) -> usize {
+        let mut ways: HashMap<Race, usize> = HashMap::new();
+
+        self.0
+            .iter()
+            .map(|race| {
+                *ways
+                    .entry(*race)
+                    .or_insert_with(|| race.get_number_of_ways_to_win())
             })
-            .collect();
+            .product()
+    }
 
-        Ok(Self(races))
+    /// The race with the fewest ways to win (the first one, in case of a tie).
+    #[cfg(test)]
+    fn hardest(&self) -> Option<&Race> {
+        self.0
+            .iter()
+            .min_by_key(|race| race.get_number_of_ways_to_win())
     }
 }
 
-fn part1(input: &[String]) -> Result<usize, AocError> {
-    let races: Races = input.try_into()?;
+/// How the numbers on the `Time:` and `Distance:` lines are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RaceParseMode {
+    /// Each number is a separate race.
+    Separate,
+    /// All the digits on a line form a single number, so there is a single race.
+    Merged,
+}
+
+fn parse_race_numbers(
+    line: &str,
+    prefix: &str,
+    mode: RaceParseMode,
+) -> Result<Vec<usize>, AocError> {
+    static WHITESPACE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
 
-    Ok(races
-        .0
-        .iter()
-        .map(|race| race.get_number_of_ways_to_win())
-        .product())
+    let numbers = line
+        .strip_prefix(prefix)
+        .map(|n| n.trim())
+        .ok_or(AocError::InvalidRaces)?;
+
+    let numbers = match mode {
+        RaceParseMode::Separate => WHITESPACE_REGEX
+            .split(numbers)
+            .map(parse_int)
+            .try_collect()?,
+        RaceParseMode::Merged => vec![parse_int(&WHITESPACE_REGEX.replace_all(numbers, ""))?],
+    };
+
+    Ok(numbers)
 }
 
-fn parse_race_2(input: &[String]) -> Result<Race, AocError> {
-    let [time_line, distance_line] = input else {
-        return Err(AocError::InvalidRaces);
+fn parse_races(input: &[String], mode: RaceParseMode) -> Result<Races, AocError> {
+    let [times, distances] = input else {
+        return Err(AocError::InvalidLineCount {
+            count: input.len(),
+            extra: input.iter().skip(2).cloned().collect(),
+        });
     };
 
-    let time = time_line
-        .strip_prefix("Time:")
-        .map(|t| t.replace(' ', ""))
-        .ok_or(AocError::InvalidRaces)?;
+    let times = parse_race_numbers(times, "Time:", mode)?;
+    let distances = parse_race_numbers(distances, "Distance:", mode)?;
 
-    let distance = distance_line
-        .strip_prefix("Distance:")
-        .map(|t| t.replace(' ', ""))
-        .ok_or(AocError::InvalidRaces)?;
+    let races = zip(times, distances)
+        .map(|(time, distance)| Race {
+            time_allowed: time,
+            distance_record: distance,
+        })
+        .collect();
 
-    Ok(Race {
-        time_allowed: time.parse()?,
-        distance_record: distance.parse()?,
-    })
+    Ok(Races(races))
+}
+
+impl TryFrom<&[String]> for Races {
+    type Error = AocError;
+
+    fn try_from(value: &[String]) -> Result<Self, Self::Error> {
+        parse_races(value, RaceParseMode::Separate)
+    }
+}
+
+fn part1(input: &[String]) -> Result<usize, AocError> {
+    let races: Races = input.try_into()?;
+
+    Ok(races.total_ways())
 }
 
 fn part2(input: &[String]) -> Result<usize, AocError> {
-    let race = parse_race_2(input)?;
+    let Races(races) = parse_races(input, RaceParseMode::Merged)?;
+    let [race] = races.as_slice() else {
+        return Err(AocError::InvalidRaces);
+    };
 
     Ok(race.get_number_of_ways_to_win())
 }
@@ -170,6 +350,277 @@ Distance:  9  40  200
         assert_eq!(races, expected_races);
     }
 
+    #[test]
+    fn test_parse_races_crlf() {
+        let input = to_lines(&EXAMPLE.replace('\n', "\r\n"));
+
+        let races: Races = input.as_slice().try_into().unwrap();
+        let expected_races: Races = to_lines(EXAMPLE).as_slice().try_into().unwrap();
+        assert_eq!(races, expected_races);
+
+        let input = to_lines("Time: 7\r\nDistance: 9\r");
+        let races = parse_races(&input, RaceParseMode::Merged).unwrap();
+        assert_eq!(
+            races,
+            Races(vec![Race {
+                time_allowed: 7,
+                distance_record: 9,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_parse_races_merged() {
+        let input = to_lines(EXAMPLE);
+        let races = parse_races(&input, RaceParseMode::Merged).unwrap();
+
+        assert_eq!(
+            races,
+            Races(vec![Race {
+                time_allowed: 71530,
+                distance_record: 940200,
+            }])
+        );
+
+        let input = to_lines("Time: 1 2\nDistance:  3   4\n");
+        let races = parse_races(&input, RaceParseMode::Merged).unwrap();
+
+        assert_eq!(
+            races,
+            Races(vec![Race {
+                time_allowed: 12,
+                distance_record: 34,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_parse_races_line_count() {
+        let input = to_lines("Time: 7\nDistance: 9\nSpeed: 3\n");
+        let error = parse_races(&input, RaceParseMode::Separate).unwrap_err();
+
+        assert!(matches!(
+            &error,
+            AocError::InvalidLineCount { count: 3, extra } if extra == &["Speed: 3"]
+        ));
+        assert_eq!(
+            error.to_string(),
+            r#"expected 2 lines, got 3; extra lines: ["Speed: 3"]"#
+        );
+
+        let input = to_lines("Time: 7\n");
+        let error = parse_races(&input, RaceParseMode::Separate).unwrap_err();
+
+        assert_eq!(error.to_string(), "expected 2 lines, got 1");
+    }
+
+    #[test]
+    fn test_total_ways_with_duplicates() {
+        let input = to_lines("Time: 7 15 7 30 15\nDistance: 9 40 9 200 40\n");
+        let races: Races = input.as_slice().try_into().unwrap();
+
+        let expected_total_ways: usize = races
+            .0
+            .iter()
+            .map(Race::get_number_of_ways_to_win)
+            .product();
+
+        assert_eq!(races.total_ways(), expected_total_ways);
+        assert_eq!(races.total_ways(), 4 * 8 * 4 * 9 * 8);
+    }
+
+    #[test]
+    fn test_hardest() {
+        let input = to_lines(EXAMPLE);
+        let races: Races = input.as_slice().try_into().unwrap();
+
+        assert_eq!(
+            races.hardest(),
+            Some(&Race {
+                time_allowed: 7,
+                distance_record: 9,
+            })
+        );
+
+        let input = to_lines("Time: 30 15 7 6\nDistance: 200 40 9 8\n");
+        let races: Races = input.as_slice().try_into().unwrap();
+
+        assert_eq!(
+            races.hardest(),
+            Some(&Race {
+                time_allowed: 6,
+                distance_record: 8,
+            })
+        );
+        assert_eq!(Races(vec![]).hardest(), None);
+    }
+
+    #[test]
+    fn test_best_distance_and_margin() {
+        let input = to_lines(EXAMPLE);
+        let races: Races = input.as_slice().try_into().unwrap();
+
+        let best_distances = races.0.iter().map(Race::best_distance).collect_vec();
+        let margins = races.0.iter().map(Race::margin_over_record).collect_vec();
+
+        assert_eq!(best_distances, vec![12, 56, 225]);
+        assert_eq!(margins, vec![3, 16, 25]);
+
+        let race = Race {
+            time_allowed: 7,
+            distance_record: 20,
+        };
+        assert_eq!(race.margin_over_record(), -8);
+    }
+
+    #[test]
+    fn test_margin_over_record_beyond_i64() {
+        // The best distance is (2^32 - 1)^2, more than `i64::MAX`.
+        let race = Race {
+            time_allowed: 2 * (1 << 32) - 2,
+            distance_record: 0,
+        };
+        assert_eq!(race.margin_over_record(), ((1i128 << 32) - 1).pow(2));
+
+        let race = Race {
+            time_allowed: 0,
+            distance_record: usize::MAX,
+        };
+        assert_eq!(race.margin_over_record(), -(usize::MAX as i128));
+    }
+
+    #[test]
+    fn test_get_number_of_ways_to_win() {
+        let ways = |time_allowed, distance_record| {
+            Race {
+                time_allowed,
+                distance_record,
+            }
+            .get_number_of_ways_to_win()
+        };
+
+        assert_eq!(ways(7, 9), 4);
+        assert_eq!(ways(15, 40), 8);
+        assert_eq!(ways(30, 200), 9);
+        assert_eq!(ways(71530, 940200), 71503);
+
+        // Perfect square discriminants: the roots are hold times exactly matching the record.
+        assert_eq!(ways(7, 10), 2);
+        assert_eq!(ways(6, 9), 0);
+        assert_eq!(ways(6, 10), 0);
+
+        for time_allowed in 0..50 {
+            for distance_record in 0..700 {
+                let expected = (1..time_allowed)
+                    .filter(|&time_held| time_held * (time_allowed - time_held) > distance_record)
+                    .count();
+
+                assert_eq!(ways(time_allowed, distance_record), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_number_of_ways_to_beat_or_tie() {
+        // Holding for 1 to 6 goes 6, 10, 12, 12, 10 and 6, so no hold time ties a record of 9.
+        let race = Race {
+            time_allowed: 7,
+            distance_record: 9,
+        };
+        assert_eq!(race.get_number_of_ways_to_win(), 4);
+        assert_eq!(race.get_number_of_ways_to_beat_or_tie(), 4);
+
+        // Holding for 2 or 5 exactly ties a record of 10.
+        let race = Race {
+            time_allowed: 7,
+            distance_record: 10,
+        };
+        assert_eq!(race.get_number_of_ways_to_win(), 2);
+        assert_eq!(race.get_number_of_ways_to_beat_or_tie(), 4);
+
+        for time_allowed in 0..30 {
+            for distance_record in 0..250 {
+                let race = Race {
+                    time_allowed,
+                    distance_record,
+                };
+                let expected = (1..time_allowed)
+                    .filter(|&time_held| time_held * (time_allowed - time_held) >= distance_record)
+                    .count();
+
+                assert_eq!(race.get_number_of_ways_to_beat_or_tie(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ways_to_win_exact() {
+        for time_allowed in (0..50).chain(1000..1010) {
+            for distance_record in (0..700).chain((200_000..250_000).step_by(997)) {
+                let race = Race {
+                    time_allowed,
+                    distance_record,
+                };
+                let expected = (1..time_allowed)
+                    .filter(|&time_held| time_held * (time_allowed - time_held) > distance_record)
+                    .count();
+
+                assert_eq!(race.ways_to_win_exact(), expected);
+            }
+        }
+
+        let race = Race {
+            time_allowed: 71530,
+            distance_record: 940200,
+        };
+        assert_eq!(race.ways_to_win_exact(), 71503);
+
+        // Holding for 1_234_567_891 exactly ties the record, well past `f64` precision.
+        let race = Race {
+            time_allowed: 10_000_000_000,
+            distance_record: 10_821_521_032_511_812_119,
+        };
+        assert_eq!(race.ways_to_win_exact(), 7_530_864_217);
+
+        // Holding for 1 goes `usize::MAX - 1`, which doesn't beat `usize::MAX - 1` but beats
+        // anything below.
+        let race = Race {
+            time_allowed: usize::MAX,
+            distance_record: usize::MAX - 1,
+        };
+        assert_eq!(race.ways_to_win_exact(), usize::MAX - 3);
+
+        let race = Race {
+            time_allowed: usize::MAX,
+            distance_record: usize::MAX - 2,
+        };
+        assert_eq!(race.ways_to_win_exact(), usize::MAX - 1);
+    }
+
+    #[test]
+    fn test_winning_hold_range() {
+        let race = Race {
+            time_allowed: 7,
+            distance_record: 9,
+        };
+        assert_eq!(race.winning_hold_range(), Some(2..=5));
+
+        let race = Race {
+            time_allowed: 6,
+            distance_record: 9,
+        };
+        assert_eq!(race.winning_hold_range(), None);
+
+        let input = to_lines(EXAMPLE);
+        let races: Races = input.as_slice().try_into().unwrap();
+
+        for race in races.0 {
+            let range = race.winning_hold_range().unwrap();
+
+            assert_eq!(range.count(), race.get_number_of_ways_to_win());
+        }
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
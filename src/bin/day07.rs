@@ -1,43 +1,121 @@
-use std::{cmp::Ordering, io, num::ParseIntError, str::FromStr};
+use std::{cmp::Ordering, collections::HashMap, env, error, fmt, io, str::FromStr};
 
-use aoc::read_lines;
+use aoc::{parse_int, read_lines, Solution};
 use itertools::Itertools;
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
+pub(crate) enum AocError {
+    Common(aoc::Error),
     InvalidCard(char),
     InvalidHand(String),
     InvalidBid(String),
+    InvalidCardOrder(String),
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::InvalidCard(card) => write!(f, "invalid card {card:?}"),
+            AocError::InvalidHand(hand) => write!(f, "invalid hand {hand:?}"),
+            AocError::InvalidBid(bid) => write!(f, "invalid bid {bid:?}"),
+            AocError::InvalidCardOrder(order) => {
+                write!(
+                    f,
+                    "invalid card order {order:?}, expected all 13 cards once"
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+impl From<aoc::Error> for AocError {
+    fn from(e: aoc::Error) -> Self {
+        Self::Common(e)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day07.txt";
+pub(crate) struct Day07;
+
+impl Solution for Day07 {
+    const INPUT_FILE: &'static str = "day07.txt";
+
+    type Output = usize;
+    type Error = AocError;
+
+    fn part1(input: &[String]) -> Result<usize, AocError> {
+        part1(input)
+    }
+
+    fn part2(input: &[String]) -> Result<usize, AocError> {
+        part2(input)
+    }
+}
 
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
 fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+    // Rank the hands with a custom card order, given weakest first as `--card-order=J23...A`.
+    if let Some(order) = env::args().find_map(|arg| {
+        arg.strip_prefix("--card-order=")
+            .map(|order| order.parse::<CardOrder>())
+    }) {
+        let order = order?;
+        let input = read_lines(aoc::cli_input_path::<Day07>())?;
+        let hands_and_bids: HandsAndBids = input.as_slice().try_into()?;
+
+        for (part, rules) in [(1, Rules::Jacks), (2, Rules::Jokers)] {
+            let total: usize = hands_and_bids
+                .ranking_by(rules, &order)
+                .iter()
+                .map(|(_, _, winnings)| winnings)
+                .sum();
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+            println!("Part {part} with card order {order}: {total}");
+        }
+
+        return Ok(());
+    }
+
+    if env::args().any(|arg| arg == "--verbose") {
+        let input = read_lines(aoc::cli_input_path::<Day07>())?;
+        let hands_and_bids: HandsAndBids = input.as_slice().try_into()?;
+
+        for (part, rules) in [(1, Rules::Jacks), (2, Rules::Jokers)] {
+            let (total, ranking) = total_winnings_with_ranking(&hands_and_bids, rules);
+
+            println!("Part {part}: {total}");
+            for (hand, rank, winnings) in ranking {
+                println!("{rank:>6} {hand} {winnings:>8}");
+            }
+        }
 
-    Ok(())
+        return Ok(());
+    }
+
+    aoc::run::<Day07>()
 }
 
+/// Cards in part 1 order, so that `card as u8` is the card's part 1 rank.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
 enum Card {
-    Two,
+    Two = 2,
     Three,
     Four,
     Five,
@@ -75,66 +153,138 @@ impl TryFrom<char> for Card {
     }
 }
 
-impl Card {
-    fn get_value_1(&self) -> usize {
-        match self {
-            Card::Two => 2,
-            Card::Three => 3,
-            Card::Four => 4,
-            Card::Five => 5,
-            Card::Six => 6,
-            Card::Seven => 7,
-            Card::Eight => 8,
-            Card::Nine => 9,
-            Card::T => 10,
-            Card::J => 11,
-            Card::Q => 12,
-            Card::K => 13,
-            Card::A => 14,
-        }
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Card::Two => '2',
+            Card::Three => '3',
+            Card::Four => '4',
+            Card::Five => '5',
+            Card::Six => '6',
+            Card::Seven => '7',
+            Card::Eight => '8',
+            Card::Nine => '9',
+            Card::T => 'T',
+            Card::J => 'J',
+            Card::Q => 'Q',
+            Card::K => 'K',
+            Card::A => 'A',
+        };
+
+        write!(f, "{symbol}")
     }
+}
 
-    fn cmp_1(&self, other: &Self) -> Ordering {
-        self.get_value_1().cmp(&other.get_value_1())
+/// Cards from weakest to strongest, to compare cards with. Each card's position in the order is
+/// looked up once, when the order is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CardOrder {
+    cards: [Card; 13],
+    /// The position of each card in `cards`, indexed by `card as usize - 2`.
+    ranks: [u8; 13],
+}
+
+impl CardOrder {
+    fn new(cards: [Card; 13]) -> Result<Self, AocError> {
+        Self::from_cards(cards).ok_or_else(|| AocError::InvalidCardOrder(cards.iter().join("")))
     }
 
-    fn get_value_2(&self) -> usize {
-        match self {
-            Card::J => 1,
-            Card::Two => 2,
-            Card::Three => 3,
-            Card::Four => 4,
-            Card::Five => 5,
-            Card::Six => 6,
-            Card::Seven => 7,
-            Card::Eight => 8,
-            Card::Nine => 9,
-            Card::T => 10,
-            Card::Q => 11,
-            Card::K => 12,
-            Card::A => 13,
+    /// The order of `cards`, or `None` if a card appears twice (and so another is missing).
+    const fn from_cards(cards: [Card; 13]) -> Option<Self> {
+        const UNRANKED: u8 = u8::MAX;
+        let mut ranks = [UNRANKED; 13];
+
+        let mut rank = 0;
+        while rank < cards.len() {
+            let index = cards[rank] as usize - 2;
+            if ranks[index] != UNRANKED {
+                return None;
+            }
+            ranks[index] = rank as u8;
+            rank += 1;
         }
+
+        Some(Self { cards, ranks })
     }
 
-    fn cmp_2(&self, other: &Self) -> Ordering {
-        self.get_value_2().cmp(&other.get_value_2())
+    /// The order of `cards` for a constant, which fails to compile if it is invalid.
+    const fn constant(cards: [Card; 13]) -> Self {
+        match Self::from_cards(cards) {
+            Some(order) => order,
+            None => panic!("a card appears twice in the order"),
+        }
+    }
+
+    fn strength(&self, card: Card) -> u8 {
+        self.ranks[card as usize - 2]
     }
 }
 
-fn cmp_cards_1(self_cards: &[Card], other_cards: &[Card]) -> Ordering {
-    for (self_card, other_card) in self_cards.iter().zip(other_cards) {
-        match self_card.cmp_1(other_card) {
-            Ordering::Equal => (),
-            order => return order,
-        }
+/// Parses an order written weakest first, such as `23456789TJQKA`.
+impl FromStr for CardOrder {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards: Vec<Card> = s.chars().map(|c| c.try_into()).try_collect()?;
+        let cards = cards
+            .try_into()
+            .map_err(|_| AocError::InvalidCardOrder(s.to_owned()))?;
+
+        Self::new(cards)
     }
+}
 
-    Ordering::Equal
+impl fmt::Display for CardOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.cards.iter().join(""))
+    }
+}
+
+const CARD_ORDER_1: CardOrder = CardOrder::constant([
+    Card::Two,
+    Card::Three,
+    Card::Four,
+    Card::Five,
+    Card::Six,
+    Card::Seven,
+    Card::Eight,
+    Card::Nine,
+    Card::T,
+    Card::J,
+    Card::Q,
+    Card::K,
+    Card::A,
+]);
+
+/// As jokers, `J` cards are the weakest.
+const CARD_ORDER_2: CardOrder = CardOrder::constant([
+    Card::J,
+    Card::Two,
+    Card::Three,
+    Card::Four,
+    Card::Five,
+    Card::Six,
+    Card::Seven,
+    Card::Eight,
+    Card::Nine,
+    Card::T,
+    Card::Q,
+    Card::K,
+    Card::A,
+]);
+
+impl Card {
+    /// The card with the part 1 rank `value` (`card as u8`), from 2 for `Two` to 14 for `A`.
+    #[cfg(test)]
+    fn from_rank_1(value: usize) -> Option<Card> {
+        CARD_ORDER_1.cards.get(value.checked_sub(2)?).copied()
+    }
 }
 
-fn cmp_cards_2(self_cards: &[Card], other_cards: &[Card]) -> Ordering {
+/// Compare cards one by one, until one of them is stronger according to `order`.
+fn cmp_cards(self_cards: &[Card], other_cards: &[Card], order: &CardOrder) -> Ordering {
     for (self_card, other_card) in self_cards.iter().zip(other_cards) {
-        match self_card.cmp_2(other_card) {
+        match order.strength(*self_card).cmp(&order.strength(*other_card)) {
             Ordering::Equal => (),
             order => return order,
         }
@@ -159,6 +309,12 @@ impl FromStr for Hand {
     }
 }
 
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().join(""))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum HandType {
     HighCard,
@@ -170,10 +326,31 @@ enum HandType {
     FiveOfAKind,
 }
 
-fn get_hand_type_from_counts(counts: std::collections::HashMap<&Card, usize>) -> HandType {
+impl HandType {
+    fn name(&self) -> &'static str {
+        match self {
+            HandType::HighCard => "High Card",
+            HandType::OnePair => "One Pair",
+            HandType::TwoPair => "Two Pair",
+            HandType::ThreeOfAKind => "Three of a Kind",
+            HandType::FullHouse => "Full House",
+            HandType::FourOfAKind => "Four of a Kind",
+            HandType::FiveOfAKind => "Five of a Kind",
+        }
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The type of a hand with these card counts, or `None` if they don't add up to five cards.
+fn get_hand_type_from_counts(counts: HashMap<&Card, usize>) -> Option<HandType> {
     let counts = counts.into_values().filter(|&c| c > 0).sorted();
 
-    match counts.as_slice() {
+    let hand_type = match counts.as_slice() {
         [5] => HandType::FiveOfAKind,
         [1, 4] => HandType::FourOfAKind,
         [2, 3] => HandType::FullHouse,
@@ -181,91 +358,272 @@ fn get_hand_type_from_counts(counts: std::collections::HashMap<&Card, usize>) ->
         [1, 2, 2] => HandType::TwoPair,
         [1, 1, 1, 2] => HandType::OnePair,
         [1, 1, 1, 1, 1] => HandType::HighCard,
-        other => panic!("Unexpected hand type '{other:?}'"),
+        _ => return None,
+    };
+
+    Some(hand_type)
+}
+
+/// The type of a hand of five `cards`, with `J` as a joker when `jokers` is set, or `None` if
+/// there aren't five cards. Jokers are always best used as more of the most common other card (or
+/// as five of a kind when all are jokers).
+fn classify(cards: &[Card], jokers: bool) -> Option<HandType> {
+    let mut counts = cards.iter().counts();
+
+    if jokers {
+        if let Some(jokers) = counts.remove(&Card::J) {
+            match counts.values_mut().max() {
+                Some(most_common) => *most_common += jokers,
+                None => {
+                    counts.insert(&Card::J, jokers);
+                }
+            }
+        }
     }
+
+    get_hand_type_from_counts(counts)
 }
 
+#[cfg(test)]
 impl Hand {
-    fn get_hand_type_1(&self) -> HandType {
-        get_hand_type_from_counts(self.0.iter().counts())
+    fn cmp_1(&self, other: &Self) -> Ordering {
+        self.cmp_by(other, false, &CARD_ORDER_1)
     }
 
-    fn cmp_1(&self, other: &Self) -> Ordering {
-        match self.get_hand_type_1().cmp(&other.get_hand_type_1()) {
+    fn cmp_2(&self, other: &Self) -> Ordering {
+        self.cmp_by(other, true, &CARD_ORDER_2)
+    }
+
+    /// Compare hands by type, with `J` as a joker when `jokers` is set, then card by card
+    /// according to `order`.
+    fn cmp_by(&self, other: &Self, jokers: bool, order: &CardOrder) -> Ordering {
+        match classify(&self.0, jokers).cmp(&classify(&other.0, jokers)) {
             Ordering::Less => Ordering::Less,
-            Ordering::Equal => cmp_cards_1(&self.0, &other.0),
+            Ordering::Equal => cmp_cards(&self.0, &other.0, order),
             Ordering::Greater => Ordering::Greater,
         }
     }
+}
 
-    fn get_hand_type_2(&self) -> HandType {
-        let counts = self.0.iter().counts();
-        if let Some(jokers) = counts.get(&Card::J) {
-            return (0..*jokers)
-                .map(|_| counts.keys())
-                .multi_cartesian_product()
-                .map(|v| {
-                    let mut counts = counts.clone();
-                    for card in v {
-                        counts.entry(&Card::J).and_modify(|c| *c -= 1);
-                        counts.entry(card).and_modify(|c| *c += 1);
-                    }
-
-                    counts
-                })
-                .map(get_hand_type_from_counts)
-                .max()
-                .unwrap();
+/// Which part's rules hands are compared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rules {
+    /// Part 1: `J` is a jack.
+    Jacks,
+    /// Part 2: `J` is a joker.
+    Jokers,
+}
+
+impl Rules {
+    #[cfg(test)]
+    fn compare(self, a: &Hand, b: &Hand) -> Ordering {
+        match self {
+            Rules::Jacks => a.cmp_1(b),
+            Rules::Jokers => a.cmp_2(b),
         }
+    }
 
-        get_hand_type_from_counts(counts)
+    fn jokers(self) -> bool {
+        self == Rules::Jokers
     }
 
-    fn cmp_2(&self, other: &Self) -> Ordering {
-        match self.get_hand_type_2().cmp(&other.get_hand_type_2()) {
-            Ordering::Less => Ordering::Less,
-            Ordering::Equal => cmp_cards_2(&self.0, &other.0),
-            Ordering::Greater => Ordering::Greater,
+    fn card_order(self) -> &'static CardOrder {
+        match self {
+            Rules::Jacks => &CARD_ORDER_1,
+            Rules::Jokers => &CARD_ORDER_2,
         }
     }
 }
 
+#[cfg(test)]
+impl Hand {
+    fn get_hand_type_1(&self) -> Option<HandType> {
+        classify(&self.0, false)
+    }
+
+    fn get_hand_type_2(&self) -> Option<HandType> {
+        classify(&self.0, true)
+    }
+
+    /// This hand's 1-based rank among `all`, as used to compute winnings. Hands that compare equal
+    /// to it don't push it down, and it doesn't need to be part of `all`.
+    fn rank_in(&self, all: &[Hand], rules: Rules) -> usize {
+        1 + all
+            .iter()
+            .filter(|other| rules.compare(other, self) == Ordering::Less)
+            .count()
+    }
+
+    fn card_counts(&self) -> HashMap<Card, usize> {
+        self.0.iter().copied().counts()
+    }
+
+    /// Whether some card appears exactly `n` times in the hand.
+    fn is_n_of_a_kind(&self, n: usize) -> bool {
+        self.card_counts().values().any(|&count| count == n)
+    }
+
+    fn is_full_house(&self) -> bool {
+        self.is_n_of_a_kind(3) && self.is_n_of_a_kind(2)
+    }
+
+    fn is_two_pair(&self) -> bool {
+        self.card_counts()
+            .values()
+            .filter(|&&count| count == 2)
+            .count()
+            == 2
+    }
+}
+
 fn parse_hand_and_bid(line: &str) -> Result<(Hand, usize), AocError> {
     let (hand, bid) = line
         .split(' ')
         .collect_tuple()
         .ok_or(AocError::InvalidBid(line.to_owned()))?;
 
-    Ok((hand.parse()?, bid.parse()?))
+    Ok((hand.parse()?, parse_int(bid)?))
+}
+
+/// Like `parse_hand_and_bid`, but rejecting a bid of 0, which can never win anything.
+#[cfg(test)]
+fn parse_hand_and_bid_strict(line: &str) -> Result<(Hand, usize), AocError> {
+    match parse_hand_and_bid(line)? {
+        (_, 0) => Err(AocError::InvalidBid(line.to_owned())),
+        hand_and_bid => Ok(hand_and_bid),
+    }
 }
 
 fn parse_hands_and_bids(input: &[String]) -> Result<Vec<(Hand, usize)>, AocError> {
     input.iter().map(|line| parse_hand_and_bid(line)).collect()
 }
 
-fn get_total_winnings<F: Fn(&Hand, &Hand) -> Ordering>(
+/// Hands with their bids, classified under both rules once when parsing rather than on every
+/// comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HandsAndBids {
+    hands_and_bids: Vec<(Hand, usize)>,
+    /// The type of each hand, under `Rules::Jacks` then `Rules::Jokers`.
+    hand_types: Vec<[HandType; 2]>,
+}
+
+impl TryFrom<&[String]> for HandsAndBids {
+    type Error = AocError;
+
+    fn try_from(value: &[String]) -> Result<Self, Self::Error> {
+        let hands_and_bids = parse_hands_and_bids(value)?;
+        let hand_types = hands_and_bids
+            .iter()
+            .map(
+                |(hand, _)| match (classify(&hand.0, false), classify(&hand.0, true)) {
+                    (Some(jacks), Some(jokers)) => Ok([jacks, jokers]),
+                    _ => Err(AocError::InvalidHand(hand.to_string())),
+                },
+            )
+            .try_collect()?;
+
+        Ok(Self {
+            hands_and_bids,
+            hand_types,
+        })
+    }
+}
+
+/// Renders the hands and bids as the input they were parsed from, one `HAND BID` line each.
+impl fmt::Display for HandsAndBids {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (hand, bid) in &self.hands_and_bids {
+            writeln!(f, "{hand} {bid}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl HandsAndBids {
+    fn hand_type(&self, index: usize, rules: Rules) -> HandType {
+        self.hand_types[index][rules.jokers() as usize]
+    }
+
+    /// The hands from weakest to strongest, each with its rank and winnings.
+    fn ranking(&self, rules: Rules) -> Vec<(&Hand, usize, usize)> {
+        self.ranking_by(rules, rules.card_order())
+    }
+
+    /// The hands ranked by type under `rules`, then card by card according to `order`.
+    fn ranking_by(&self, rules: Rules, order: &CardOrder) -> Vec<(&Hand, usize, usize)> {
+        let mut indices = (0..self.hands_and_bids.len()).collect_vec();
+
+        let cards = |index: usize| {
+            let (Hand(cards), _) = &self.hands_and_bids[index];
+            cards
+        };
+
+        indices.sort_unstable_by(|&a, &b| {
+            self.hand_type(a, rules)
+                .cmp(&self.hand_type(b, rules))
+                .then_with(|| cmp_cards(cards(a), cards(b), order))
+        });
+
+        indices
+            .into_iter()
+            .enumerate()
+            .map(|(i, index)| {
+                let (hand, bid) = &self.hands_and_bids[index];
+
+                (hand, i + 1, (i + 1) * bid)
+            })
+            .collect()
+    }
+}
+
+/// Sort the hands from weakest to strongest, pairing each with its rank and winnings.
+#[cfg(test)]
+fn rank_hands<F: Fn(&Hand, &Hand) -> Ordering>(
     mut hands_and_bids: Vec<(Hand, usize)>,
     compare: F,
-) -> usize {
+) -> Vec<(Hand, usize, usize)> {
     hands_and_bids.sort_unstable_by(|(a, _), (b, _)| compare(a, b));
 
     hands_and_bids
-        .iter()
+        .into_iter()
         .enumerate()
-        .map(|(i, &(_, bid))| (i + 1) * bid)
+        .map(|(i, (hand, bid))| (hand, i + 1, (i + 1) * bid))
+        .collect()
+}
+
+#[cfg(test)]
+fn get_total_winnings<F: Fn(&Hand, &Hand) -> Ordering>(
+    hands_and_bids: Vec<(Hand, usize)>,
+    compare: F,
+) -> usize {
+    rank_hands(hands_and_bids, compare)
+        .iter()
+        .map(|(_, _, winnings)| winnings)
         .sum()
 }
 
+/// The total winnings, along with every hand and its rank and winnings from weakest to strongest.
+fn total_winnings_with_ranking(
+    hands_and_bids: &HandsAndBids,
+    rules: Rules,
+) -> (usize, Vec<(&Hand, usize, usize)>) {
+    let ranking = hands_and_bids.ranking(rules);
+    let total = ranking.iter().map(|(_, _, winnings)| winnings).sum();
+
+    (total, ranking)
+}
+
 fn part1(input: &[String]) -> Result<usize, AocError> {
-    let hands_and_bids = parse_hands_and_bids(input)?;
+    let hands_and_bids: HandsAndBids = input.try_into()?;
 
-    Ok(get_total_winnings(hands_and_bids, Hand::cmp_1))
+    Ok(total_winnings_with_ranking(&hands_and_bids, Rules::Jacks).0)
 }
 
 fn part2(input: &[String]) -> Result<usize, AocError> {
-    let hands_and_bids = parse_hands_and_bids(input)?;
+    let hands_and_bids: HandsAndBids = input.try_into()?;
 
-    Ok(get_total_winnings(hands_and_bids, Hand::cmp_2))
+    Ok(total_winnings_with_ranking(&hands_and_bids, Rules::Jokers).0)
 }
 
 #[cfg(test)]
@@ -273,11 +631,145 @@ mod tests {
     use super::*;
 
     use aoc::to_lines;
+    use proptest::prelude::*;
+
+    const ALL_CARDS: [Card; 13] = [
+        Card::Two,
+        Card::Three,
+        Card::Four,
+        Card::Five,
+        Card::Six,
+        Card::Seven,
+        Card::Eight,
+        Card::Nine,
+        Card::T,
+        Card::J,
+        Card::Q,
+        Card::K,
+        Card::A,
+    ];
+
+    #[test]
+    fn test_card_order_1() {
+        // The part 1 order, weakest first, as it was listed by `get_value_1`.
+        let expected: Vec<Card> = "23456789TJQKA"
+            .chars()
+            .map(Card::try_from)
+            .try_collect()
+            .unwrap();
+
+        for (rank, &card) in (2u8..).zip(&expected) {
+            assert_eq!(card as u8, rank);
+        }
+
+        let ranked = (0..).zip(&expected);
+        for ((i, &a), (j, &b)) in ranked.clone().cartesian_product(ranked) {
+            let order = CARD_ORDER_1.strength(a).cmp(&CARD_ORDER_1.strength(b));
+            assert_eq!(order, i.cmp(&j), "{a} vs {b}");
+        }
+
+        let mut sorted = ALL_CARDS;
+        sorted.reverse();
+        sorted.sort_by_key(|&card| CARD_ORDER_1.strength(card));
+        assert_eq!(sorted.to_vec(), expected);
+        assert_eq!(CARD_ORDER_1.to_string(), "23456789TJQKA");
+    }
+
+    #[test]
+    fn test_card_order_2() {
+        assert_eq!(CARD_ORDER_2.strength(Card::J), 0);
+        assert_eq!(CARD_ORDER_2.strength(Card::Two), 1);
+        assert_eq!(CARD_ORDER_2.strength(Card::T), 9);
+        assert_eq!(CARD_ORDER_2.strength(Card::Q), 10);
+        assert_eq!(CARD_ORDER_2.strength(Card::A), 12);
+    }
+
+    #[test]
+    fn test_card_order_parse() {
+        assert_eq!("23456789TJQKA".parse::<CardOrder>().unwrap(), CARD_ORDER_1);
+        assert_eq!("J23456789TQKA".parse::<CardOrder>().unwrap(), CARD_ORDER_2);
+
+        // A card twice, and so another missing.
+        assert!(matches!(
+            "23456789TJQKK".parse::<CardOrder>(),
+            Err(AocError::InvalidCardOrder(order)) if order == "23456789TJQKK"
+        ));
+        assert!(matches!(
+            "23456789TJQK".parse::<CardOrder>(),
+            Err(AocError::InvalidCardOrder(_))
+        ));
+        assert!(matches!(
+            "23456789TJQKAA".parse::<CardOrder>(),
+            Err(AocError::InvalidCardOrder(_))
+        ));
+        assert!(matches!(
+            "23456789TJQK1".parse::<CardOrder>(),
+            Err(AocError::InvalidCard('1'))
+        ));
+
+        let mut cards = ALL_CARDS;
+        cards[0] = Card::A;
+        assert!(matches!(
+            CardOrder::new(cards),
+            Err(AocError::InvalidCardOrder(_))
+        ));
+    }
+
+    #[test]
+    fn test_card_from_rank_1() {
+        for card in ALL_CARDS {
+            assert_eq!(Card::from_rank_1(card as usize), Some(card));
+        }
+
+        assert_eq!(Card::from_rank_1(0), None);
+        assert_eq!(Card::from_rank_1(1), None);
+        assert_eq!(Card::from_rank_1(15), None);
+    }
+
+    #[test]
+    fn test_hand_display() {
+        let hand: Hand = "QQQJA".parse().unwrap();
+
+        assert_eq!(format!("{hand}"), "QQQJA");
+        assert_eq!(classify(&hand.0, false), Some(HandType::ThreeOfAKind));
+        assert_eq!(classify(&hand.0, true), Some(HandType::FourOfAKind));
+    }
+
+    #[test]
+    fn test_classify_not_five_cards() {
+        for cards in ["", "QQJA", "QQQJA2"] {
+            let cards: Vec<Card> = cards.chars().map(|c| c.try_into().unwrap()).collect();
+
+            assert_eq!(classify(&cards, false), None);
+            assert_eq!(classify(&cards, true), None);
+        }
+
+        assert_eq!(classify(&[Card::J; 4], true), None);
+        assert_eq!(classify(&[Card::J; 5], true), Some(HandType::FiveOfAKind));
+    }
 
     #[test]
     fn test_get_hand_type_1() {
         let hand: Hand = "QQQJA".parse().unwrap();
-        assert_eq!(hand.get_hand_type_1(), HandType::ThreeOfAKind);
+        assert_eq!(hand.get_hand_type_1(), Some(HandType::ThreeOfAKind));
+    }
+
+    #[test]
+    fn test_hand_type_name() {
+        let names = [
+            (HandType::HighCard, "High Card"),
+            (HandType::OnePair, "One Pair"),
+            (HandType::TwoPair, "Two Pair"),
+            (HandType::ThreeOfAKind, "Three of a Kind"),
+            (HandType::FullHouse, "Full House"),
+            (HandType::FourOfAKind, "Four of a Kind"),
+            (HandType::FiveOfAKind, "Five of a Kind"),
+        ];
+
+        for (hand_type, name) in names {
+            assert_eq!(hand_type.name(), name);
+            assert_eq!(hand_type.to_string(), name);
+        }
     }
 
     #[test]
@@ -293,10 +785,53 @@ mod tests {
         assert_eq!(hand0.cmp_1(&hand1), Ordering::Greater);
     }
 
+    #[test]
+    fn test_hand_cmp_by_reversed_order() {
+        let mut cards = ALL_CARDS;
+        cards.reverse();
+        let reversed = CardOrder::new(cards).unwrap();
+        assert_eq!(reversed.to_string(), "AKQJT98765432");
+
+        let hand0: Hand = "2AAAA".parse().unwrap();
+        let hand1: Hand = "33332".parse().unwrap();
+
+        assert_eq!(hand0.cmp_1(&hand1), Ordering::Less);
+        assert_eq!(hand0.cmp_by(&hand1, false, &reversed), Ordering::Greater);
+
+        // The type still comes first.
+        let hand1: Hand = "AAAAA".parse().unwrap();
+        assert_eq!(hand0.cmp_by(&hand1, false, &reversed), Ordering::Less);
+
+        let input = to_lines(EXAMPLE);
+        let hands_and_bids = parse_hands_and_bids(&input).unwrap();
+
+        // Only `KK677` and `KTJJT` (both two pair) swap places, and `T55J5` and `QQQJA`.
+        let winnings = get_total_winnings(hands_and_bids, |a, b| a.cmp_by(b, false, &reversed));
+        assert_eq!(winnings, 765 + 28 * 2 + 220 * 3 + 483 * 4 + 684 * 5);
+    }
+
     #[test]
     fn test_get_hand_type_2() {
         let hand: Hand = "QJJQ2".parse().unwrap();
-        assert_eq!(hand.get_hand_type_2(), HandType::FourOfAKind);
+        assert_eq!(hand.get_hand_type_2(), Some(HandType::FourOfAKind));
+    }
+
+    #[test]
+    fn test_get_hand_type_2_jokers() {
+        let cases = [
+            ("JJJJJ", HandType::FiveOfAKind),
+            ("J2345", HandType::OnePair),
+            ("JJ234", HandType::ThreeOfAKind),
+            ("J2233", HandType::FullHouse),
+            ("JJJ2K", HandType::FourOfAKind),
+            ("23456", HandType::HighCard),
+        ];
+
+        for (hand, hand_type) in cases {
+            let hand: Hand = hand.parse().unwrap();
+
+            assert_eq!(hand.get_hand_type_2(), Some(hand_type));
+        }
     }
 
     #[test]
@@ -307,6 +842,84 @@ mod tests {
         assert_eq!(hand0.cmp_2(&hand1), Ordering::Greater);
     }
 
+    #[test]
+    fn test_hand_predicates() {
+        let three_of_a_kind: Hand = "QQQJA".parse().unwrap();
+        assert!(three_of_a_kind.is_n_of_a_kind(3));
+        assert!(!three_of_a_kind.is_n_of_a_kind(2));
+        assert!(!three_of_a_kind.is_full_house());
+        assert!(!three_of_a_kind.is_two_pair());
+
+        let two_pair: Hand = "KTJJT".parse().unwrap();
+        assert!(two_pair.is_n_of_a_kind(2));
+        assert!(two_pair.is_two_pair());
+        assert!(!two_pair.is_full_house());
+
+        let one_pair: Hand = "32T3K".parse().unwrap();
+        assert!(one_pair.is_n_of_a_kind(2));
+        assert!(!one_pair.is_two_pair());
+
+        let full_house: Hand = "77888".parse().unwrap();
+        assert!(full_house.is_full_house());
+        assert!(full_house.is_n_of_a_kind(3));
+        assert!(!full_house.is_two_pair());
+
+        let five_of_a_kind: Hand = "AAAAA".parse().unwrap();
+        assert!(five_of_a_kind.is_n_of_a_kind(5));
+        assert!(!five_of_a_kind.is_n_of_a_kind(4));
+    }
+
+    fn hand_strategy() -> impl Strategy<Value = Hand> {
+        prop::collection::vec(prop::sample::select(ALL_CARDS.to_vec()), 5).prop_map(Hand)
+    }
+
+    fn assert_transitive<F: Fn(&Hand, &Hand) -> Ordering>(
+        a: &Hand,
+        b: &Hand,
+        c: &Hand,
+        compare: F,
+    ) -> Result<(), TestCaseError> {
+        if compare(a, b) != Ordering::Greater && compare(b, c) != Ordering::Greater {
+            prop_assert_ne!(compare(a, c), Ordering::Greater);
+        }
+
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_hand_cmp_antisymmetric(a in hand_strategy(), b in hand_strategy()) {
+            prop_assert_eq!(a.cmp_1(&b), b.cmp_1(&a).reverse());
+            prop_assert_eq!(a.cmp_2(&b), b.cmp_2(&a).reverse());
+        }
+
+        #[test]
+        fn test_hand_display_round_trip(hand in hand_strategy()) {
+            let displayed = format!("{hand}");
+
+            prop_assert_eq!(displayed.parse::<Hand>().unwrap(), hand);
+        }
+
+        #[test]
+        fn test_classify(hand in hand_strategy()) {
+            prop_assert_eq!(classify(&hand.0, false), hand.get_hand_type_1());
+            prop_assert_eq!(classify(&hand.0, true), hand.get_hand_type_2());
+            prop_assert!(classify(&hand.0, true) >= classify(&hand.0, false));
+        }
+
+        #[test]
+        fn test_hand_cmp_transitive(
+            a in hand_strategy(),
+            b in hand_strategy(),
+            c in hand_strategy(),
+        ) {
+            for (x, y, z) in [&a, &b, &c].into_iter().permutations(3).map(|p| (p[0], p[1], p[2])) {
+                assert_transitive(x, y, z, Hand::cmp_1)?;
+                assert_transitive(x, y, z, Hand::cmp_2)?;
+            }
+        }
+    }
+
     // Make sure to remove any extra indentation (otherwise it will be part of the string)
     const EXAMPLE: &str = "\
 32T3K 765
@@ -316,6 +929,129 @@ KTJJT 220
 QQQJA 483
 ";
 
+    #[test]
+    fn test_parse_hand_and_bid_strict() {
+        assert_eq!(
+            parse_hand_and_bid_strict("AAAAA 1").unwrap(),
+            ("AAAAA".parse().unwrap(), 1)
+        );
+        assert!(parse_hand_and_bid("AAAAA 0").is_ok());
+        assert!(matches!(
+            parse_hand_and_bid_strict("AAAAA 0"),
+            Err(AocError::InvalidBid(line)) if line == "AAAAA 0"
+        ));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = "32T3X".parse::<Hand>().unwrap_err();
+        assert_eq!(error.to_string(), "invalid card 'X'");
+
+        let error = parse_hand_and_bid_strict("AAAAA 0").unwrap_err();
+        assert_eq!(error.to_string(), r#"invalid bid "AAAAA 0""#);
+    }
+
+    #[test]
+    fn test_hand_rank_in() {
+        let input = to_lines(EXAMPLE);
+        let hands = parse_hands_and_bids(&input)
+            .unwrap()
+            .into_iter()
+            .map(|(hand, _)| hand)
+            .collect_vec();
+
+        let hand: Hand = "KTJJT".parse().unwrap();
+        assert_eq!(hand.rank_in(&hands, Rules::Jacks), 2);
+        assert_eq!(hand.rank_in(&hands, Rules::Jokers), 5);
+
+        let hand: Hand = "32T3K".parse().unwrap();
+        assert_eq!(hand.rank_in(&hands, Rules::Jacks), 1);
+        assert_eq!(hand.rank_in(&hands, Rules::Jokers), 1);
+    }
+
+    #[test]
+    fn test_hands_and_bids_hand_types() {
+        let input = to_lines(EXAMPLE);
+        let hands_and_bids: HandsAndBids = input.as_slice().try_into().unwrap();
+
+        for (index, (hand, _)) in hands_and_bids.hands_and_bids.iter().enumerate() {
+            assert_eq!(
+                Some(hands_and_bids.hand_type(index, Rules::Jacks)),
+                hand.get_hand_type_1()
+            );
+            assert_eq!(
+                Some(hands_and_bids.hand_type(index, Rules::Jokers)),
+                hand.get_hand_type_2()
+            );
+        }
+    }
+
+    #[test]
+    fn test_hands_and_bids_display() {
+        let input = to_lines(EXAMPLE);
+        let hands_and_bids: HandsAndBids = input.as_slice().try_into().unwrap();
+        let displayed = hands_and_bids.to_string();
+
+        assert_eq!(displayed, EXAMPLE);
+
+        let reparsed: HandsAndBids = to_lines(&displayed).as_slice().try_into().unwrap();
+        assert_eq!(reparsed, hands_and_bids);
+    }
+
+    proptest! {
+        #[test]
+        fn test_hands_and_bids_ranking(hands in prop::collection::vec(hand_strategy(), 0..20)) {
+            let input = hands
+                .iter()
+                .enumerate()
+                .map(|(bid, hand)| format!("{hand} {bid}"))
+                .collect_vec();
+            let hands_and_bids: HandsAndBids = input.as_slice().try_into().unwrap();
+
+            for rules in [Rules::Jacks, Rules::Jokers] {
+                let expected = get_total_winnings(
+                    parse_hands_and_bids(&input).unwrap(),
+                    |a, b| rules.compare(a, b),
+                );
+
+                prop_assert_eq!(total_winnings_with_ranking(&hands_and_bids, rules).0, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_total_winnings_with_ranking() {
+        let input = to_lines(EXAMPLE);
+        let hands_and_bids: HandsAndBids = input.as_slice().try_into().unwrap();
+
+        let (total, ranking) = total_winnings_with_ranking(&hands_and_bids, Rules::Jacks);
+        let leaderboard = ranking
+            .iter()
+            .map(|(hand, rank, winnings)| (hand.to_string(), *rank, *winnings))
+            .collect_vec();
+
+        assert_eq!(total, 6440);
+        assert_eq!(
+            leaderboard,
+            vec![
+                ("32T3K".to_owned(), 1, 765),
+                ("KTJJT".to_owned(), 2, 440),
+                ("KK677".to_owned(), 3, 84),
+                ("T55J5".to_owned(), 4, 2736),
+                ("QQQJA".to_owned(), 5, 2415),
+            ]
+        );
+
+        let (total, ranking) = total_winnings_with_ranking(&hands_and_bids, Rules::Jokers);
+        let order = ranking
+            .iter()
+            .map(|(hand, ..)| hand.to_string())
+            .collect_vec();
+
+        assert_eq!(total, 5905);
+        assert_eq!(order, vec!["32T3K", "KK677", "T55J5", "QQQJA", "KTJJT"]);
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
@@ -1,33 +1,89 @@
-use std::{collections::HashMap, io};
-
-use aoc::read_lines;
+use std::{
+    collections::HashMap,
+    env, error,
+    fmt::{self, Write},
+    io,
+};
+
+use aoc::{read_lines, Solution};
 use itertools::Itertools;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
+pub(crate) enum AocError {
+    Common(aoc::Error),
     InvalidMove(char),
     InvalidNetworkEntry(String),
     InvalidMap(String),
+    /// No node ending with `Z` can be reached from `AAA`.
+    NoPath,
+    /// The ghosts are never all on a node ending with `Z` at the same time.
+    NoCommonEnd,
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::InvalidMove(m) => write!(f, "invalid move {m:?}"),
+            AocError::InvalidNetworkEntry(entry) => write!(f, "invalid network entry {entry:?}"),
+            AocError::InvalidMap(map) => write!(f, "invalid map {map:?}"),
+            AocError::NoPath => write!(f, "no node ending with Z can be reached from AAA"),
+            AocError::NoCommonEnd => {
+                write!(
+                    f,
+                    "the ghosts are never all on a node ending with Z at once"
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-const INPUT_PATH: &str = "inputs/day08.txt";
+pub(crate) struct Day08;
+
+impl Solution for Day08 {
+    const INPUT_FILE: &'static str = "day08.txt";
+
+    type Output = usize;
+    type Error = AocError;
+
+    fn part1(input: &[String]) -> Result<usize, AocError> {
+        part1(input)
+    }
+
+    fn part2(input: &[String]) -> Result<usize, AocError> {
+        part2(input)
+    }
+}
 
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
 fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+    if env::args().any(|arg| arg == "--dot") {
+        let input = read_lines(aoc::cli_input_path::<Day08>())?;
+        let map: Map = input.as_slice().try_into()?;
+        print!("{}", map.to_dot());
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+        return Ok(());
+    }
 
-    Ok(())
+    aoc::run::<Day08>()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -69,10 +125,17 @@ fn parse_network_entry(line: &str) -> Result<(String, (String, String)), AocErro
 impl TryFrom<&[String]> for Map {
     type Error = AocError;
 
+    /// The moves may be split across several lines, which are joined up to the first blank line.
     fn try_from(value: &[String]) -> Result<Self, Self::Error> {
-        match value {
-            [moves, space, network @ ..] if space.is_empty() => {
-                let moves = moves.chars().map(|c| c.try_into()).try_collect()?;
+        let blank = value.iter().position(|line| line.is_empty());
+
+        match blank.map(|index| value.split_at(index)) {
+            Some((moves @ [_, ..], [_, network @ ..])) => {
+                let moves = moves
+                    .iter()
+                    .flat_map(|line| line.chars())
+                    .map(|c| c.try_into())
+                    .try_collect()?;
                 let network = network
                     .iter()
                     .map(|s| parse_network_entry(s))
@@ -90,42 +153,384 @@ impl Map {
         self.moves[steps % self.moves.len()]
     }
 
-    fn next_position(&self, current_move: Move, current_position: &str) -> &str {
-        let (left, right) = &self.network[current_position];
+    /// The position after making `current_move` from `current_position`, or `None` if either of
+    /// them is not in the network.
+    fn next_position(&self, current_move: Move, current_position: &str) -> Option<&str> {
+        let (left, right) = self.network.get(current_position)?;
 
-        match current_move {
+        let next_position = match current_move {
             Move::Left => left,
             Move::Right => right,
+        };
+
+        self.network
+            .contains_key(next_position)
+            .then_some(next_position)
+    }
+
+    /// The entry of `node` in the network, as it is written in the input.
+    fn entry(&self, node: &str) -> String {
+        match self.network.get(node) {
+            Some((left, right)) => format!("{node} = ({left}, {right})"),
+            None => node.to_owned(),
         }
     }
+
+    /// The number of moves, and how many of them are left and right moves.
+    #[cfg(test)]
+    fn instruction_summary(&self) -> (usize, usize, usize) {
+        let left = self.moves.iter().filter(|&&m| m == Move::Left).count();
+
+        (self.moves.len(), left, self.moves.len() - left)
+    }
+
+    /// The part of the network within `depth` steps (left or right) of `start`, keeping the same
+    /// moves. Nodes at the boundary keep their edges, which may lead outside of the new network.
+    #[cfg(test)]
+    fn neighborhood(&self, start: &str, depth: usize) -> Map {
+        use std::collections::VecDeque;
+
+        let mut network = HashMap::new();
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((node, distance)) = queue.pop_front() {
+            let Some((left, right)) = self.network.get(node) else {
+                continue;
+            };
+
+            if network.contains_key(node) {
+                continue;
+            }
+
+            network.insert(node.to_owned(), (left.clone(), right.clone()));
+
+            if distance < depth {
+                queue.push_back((left, distance + 1));
+                queue.push_back((right, distance + 1));
+            }
+        }
+
+        Map {
+            moves: self.moves.clone(),
+            network,
+        }
+    }
+
+    /// Render the network as a Graphviz digraph, with `L` edges solid and `R` edges dashed.
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+
+        for (node, (left, right)) in self.network.iter().sorted() {
+            writeln!(dot, "    \"{node}\" -> \"{left}\" [style=solid];").unwrap();
+            writeln!(dot, "    \"{node}\" -> \"{right}\" [style=dashed];").unwrap();
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+/// The map with its nodes interned, and where each node leads precomputed for every position in
+/// the moves, so that walks don't hash node names or look up moves at every step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Transitions {
+    nodes: Vec<String>,
+    indices: HashMap<String, usize>,
+    moves_len: usize,
+    /// `next[node * moves_len + move_index]` is the node that `node` leads to at `move_index`.
+    next: Vec<usize>,
+}
+
+impl Transitions {
+    /// Fails with `InvalidMap` if a node leads to a node missing from the network.
+    fn new(map: &Map) -> Result<Self, AocError> {
+        let nodes = map.network.keys().sorted().cloned().collect_vec();
+        let indices: HashMap<_, _> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.clone(), index))
+            .collect();
+
+        let mut next = Vec::with_capacity(nodes.len() * map.moves.len());
+
+        for node in &nodes {
+            let (left, right) = &map.network[node];
+            let (Some(&left), Some(&right)) = (indices.get(left), indices.get(right)) else {
+                return Err(AocError::InvalidMap(map.entry(node)));
+            };
+
+            next.extend(map.moves.iter().map(|current_move| match current_move {
+                Move::Left => left,
+                Move::Right => right,
+            }));
+        }
+
+        Ok(Self {
+            nodes,
+            indices,
+            moves_len: map.moves.len(),
+            next,
+        })
+    }
+
+    /// The node and move index after making the move at `move_index` from `node`.
+    fn step(&self, node: usize, move_index: usize) -> (usize, usize) {
+        let next_node = self.next[node * self.moves_len + move_index];
+
+        (next_node, (move_index + 1) % self.moves_len)
+    }
 }
 
-fn steps_to_end(map: &Map, starting_pos: &str) -> usize {
-    let mut pos = starting_pos;
+/// Like `steps_to_end`, but walking through precomputed `transitions`.
+#[cfg(test)]
+fn steps_to_end_cached(transitions: &Transitions, starting_pos: &str) -> Option<usize> {
+    let mut node = *transitions.indices.get(starting_pos)?;
+    let mut move_index = 0;
     let mut steps = 0;
 
-    while !pos.ends_with('Z') {
-        pos = map.next_position(map.get_move_at(steps), pos);
+    // Past this many steps, some (node, move index) state has been seen twice.
+    let max_steps = transitions.next.len();
+
+    while !transitions.nodes[node].ends_with('Z') {
+        if steps >= max_steps {
+            return None;
+        }
+
+        (node, move_index) = transitions.step(node, move_index);
         steps += 1;
     }
 
-    steps
+    Some(steps)
+}
+
+/// The number of steps from `starting_pos` to a node ending with `Z`, or `None` if there is no
+/// such node in the network or none can be reached from it. Fails like `steps_until`.
+fn steps_to_end(map: &Map, starting_pos: &str) -> Result<Option<usize>, AocError> {
+    steps_until(map, starting_pos, |pos| pos.ends_with('Z'))
+}
+
+/// The number of steps from `start` to a node for which `is_end` holds, or `None` if `start` is
+/// not in the network or no such node can be reached from it. Fails with `InvalidMap` if the walk
+/// gets to a node leading to a node missing from the network.
+fn steps_until<F: Fn(&str) -> bool>(
+    map: &Map,
+    start: &str,
+    is_end: F,
+) -> Result<Option<usize>, AocError> {
+    if !map.network.contains_key(start) {
+        return Ok(None);
+    }
+
+    // Past this many steps, some (position, move index) state has been seen twice.
+    let max_steps = map.network.len() * map.moves.len();
+
+    let mut pos = start;
+    let mut steps = 0;
+
+    while !is_end(pos) {
+        if steps >= max_steps {
+            return Ok(None);
+        }
+
+        pos = map
+            .next_position(map.get_move_at(steps), pos)
+            .ok_or_else(|| AocError::InvalidMap(map.entry(pos)))?;
+        steps += 1;
+    }
+
+    Ok(Some(steps))
+}
+
+/// How a walk ends up looping: after `tail` steps, the walk repeats every `period` steps. The
+/// walk is on a node ending with `Z` after each of the `z_offsets` steps (before `tail + period`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cycle {
+    tail: usize,
+    period: usize,
+    z_offsets: Vec<usize>,
+}
+
+impl Cycle {
+    /// Whether the walk is on a `Z` node exactly at every multiple of the period, which makes the
+    /// first common step of clean cycles the LCM of their periods. The first `Z` visit must be
+    /// part of the loop for later multiples to be visits too.
+    fn is_clean(&self) -> bool {
+        self.z_offsets == [self.period] && self.period >= self.tail
+    }
+
+    /// Whether the walk is on a `Z` node after `steps`.
+    fn is_at_end(&self, steps: usize) -> bool {
+        self.z_offsets.iter().any(|&offset| {
+            if offset < self.tail {
+                steps == offset
+            } else {
+                steps >= offset && (steps - offset).is_multiple_of(self.period)
+            }
+        })
+    }
+}
+
+/// Walk from `starting_pos` until a `(position, move index)` state repeats.
+fn find_cycle(transitions: &Transitions, starting_pos: &str) -> Cycle {
+    let mut seen = vec![None; transitions.next.len()];
+    let mut z_offsets = vec![];
+
+    let mut node = transitions.indices[starting_pos];
+    let mut move_index = 0;
+
+    for steps in 0.. {
+        let state = node * transitions.moves_len + move_index;
+
+        if let Some(tail) = seen[state] {
+            return Cycle {
+                tail,
+                period: steps - tail,
+                z_offsets,
+            };
+        }
+
+        seen[state] = Some(steps);
+        if transitions.nodes[node].ends_with('Z') {
+            z_offsets.push(steps);
+        }
+
+        (node, move_index) = transitions.step(node, move_index);
+    }
+
+    unreachable!("there are finitely many states")
+}
+
+impl Map {
+    /// The number of steps until the walk from `start` has been on a node ending with `Z` `done`
+    /// times (counting `start` itself), or `None` if that never happens or if the network has a
+    /// node leading to a missing node.
+    ///
+    /// Only the first loop of the walk is followed: later visits are found by skipping whole
+    /// periods, so this takes the same time for billions of steps as for a few.
+    #[cfg(test)]
+    fn steps_fast(&self, start: &str, done: usize) -> Option<usize> {
+        let Some(visit) = done.checked_sub(1) else {
+            return Some(0);
+        };
+
+        if !self.network.contains_key(start) {
+            return None;
+        }
+
+        let cycle = find_cycle(&Transitions::new(self).ok()?, start);
+        let (once, looping): (Vec<_>, Vec<_>) = cycle
+            .z_offsets
+            .iter()
+            .partition(|&&offset| offset < cycle.tail);
+
+        if let Some(&steps) = once.get(visit) {
+            return Some(steps);
+        }
+
+        let visit = visit - once.len();
+        let &offset = looping.get(visit % looping.len().max(1))?;
+
+        (visit / looping.len())
+            .checked_mul(cycle.period)?
+            .checked_add(offset)
+    }
+}
+
+/// The smallest `n >= start` with `n = a (mod m)` and `n = b (mod p)`, and the period of such `n`.
+fn solve_congruences(
+    a: usize,
+    m: usize,
+    b: usize,
+    p: usize,
+    start: usize,
+) -> Option<(usize, usize)> {
+    use num::Integer;
+
+    let (a, m, b, p) = (a as i128, m as i128, b as i128, p as i128);
+    let gcd = m.extended_gcd(&p);
+
+    if (b - a) % gcd.gcd != 0 {
+        return None;
+    }
+
+    let lcm = m / gcd.gcd * p;
+    let k = ((b - a) / gcd.gcd * gcd.x).rem_euclid(p / gcd.gcd);
+    let n = (a + m * k).rem_euclid(lcm) as usize;
+    let lcm = lcm as usize;
+
+    Some((n + (start.saturating_sub(n)).div_ceil(lcm) * lcm, lcm))
+}
+
+/// The first step at which every cycle is on a `Z` node, if any.
+fn first_common_end(cycles: &[Cycle]) -> Option<usize> {
+    // Steps in a tail only happen once, so they can be checked one by one.
+    let once = cycles
+        .iter()
+        .flat_map(|cycle| {
+            cycle
+                .z_offsets
+                .iter()
+                .filter(|&&offset| offset < cycle.tail)
+        })
+        .copied()
+        .filter(|&offset| cycles.iter().all(|cycle| cycle.is_at_end(offset)));
+
+    // Steps in the loops are arithmetic progressions `(start, period)`, intersected cycle by
+    // cycle.
+    let looping = |cycle: &Cycle| {
+        let offsets = cycle.z_offsets.iter().copied();
+
+        offsets
+            .filter(|&offset| offset >= cycle.tail)
+            .map(|offset| (offset, cycle.period))
+            .collect_vec()
+    };
+
+    let (first, rest) = cycles.split_first()?;
+    let progressions = rest.iter().fold(looping(first), |progressions, cycle| {
+        progressions
+            .into_iter()
+            .cartesian_product(looping(cycle))
+            .filter_map(|((a, m), (b, p))| solve_congruences(a, m, b, p, a.max(b)))
+            .collect()
+    });
+
+    once.chain(progressions.into_iter().map(|(start, _)| start))
+        .min()
 }
 
 fn steps_to_end_2<S: AsRef<str>, Positions: IntoIterator<Item = S>>(
     map: &Map,
     starting_positions: Positions,
-) -> usize {
-    starting_positions
+) -> Result<Option<usize>, AocError> {
+    let transitions = Transitions::new(map)?;
+
+    let cycles = starting_positions
         .into_iter()
-        .map(|pos| steps_to_end(map, pos.as_ref()))
-        .fold(1, num::integer::lcm)
+        .map(|pos| find_cycle(&transitions, pos.as_ref()))
+        .collect_vec();
+
+    if cycles.is_empty() {
+        return Ok(None);
+    }
+
+    if cycles.iter().all(Cycle::is_clean) {
+        return Ok(Some(
+            cycles
+                .iter()
+                .map(|cycle| cycle.period)
+                .fold(1, num::integer::lcm),
+        ));
+    }
+
+    Ok(first_common_end(&cycles))
 }
 
 fn part1(input: &[String]) -> Result<usize, AocError> {
     let map: Map = input.try_into()?;
 
-    Ok(steps_to_end(&map, "AAA"))
+    steps_to_end(&map, "AAA")?.ok_or(AocError::NoPath)
 }
 
 fn part2(input: &[String]) -> Result<usize, AocError> {
@@ -133,7 +538,7 @@ fn part2(input: &[String]) -> Result<usize, AocError> {
 
     let starting_positions = map.network.keys().filter(|key| key.ends_with('A'));
 
-    Ok(steps_to_end_2(&map, starting_positions))
+    steps_to_end_2(&map, starting_positions)?.ok_or(AocError::NoCommonEnd)
 }
 
 #[cfg(test)]
@@ -168,6 +573,79 @@ ZZZ = (ZZZ, ZZZ)
         assert_eq!(map, expected_map)
     }
 
+    #[test]
+    fn test_parse_map_multiline_moves() {
+        let input = to_lines("LL\nR\n\nAAA = (AAA, AAA)\n");
+
+        let map: Map = (input.as_slice()).try_into().unwrap();
+
+        assert_eq!(map.moves, vec![Move::Left, Move::Left, Move::Right]);
+        assert_eq!(map.network.len(), 1);
+
+        let input = to_lines("\nAAA = (AAA, AAA)\n");
+        let result: Result<Map, _> = input.as_slice().try_into();
+        assert!(matches!(result, Err(AocError::InvalidMap(_))));
+    }
+
+    #[test]
+    fn test_parse_map_crlf() {
+        let input = to_lines(&EXAMPLE.replace('\n', "\r\n"));
+
+        let map: Map = (input.as_slice()).try_into().unwrap();
+        let expected_map: Map = (to_lines(EXAMPLE).as_slice()).try_into().unwrap();
+        assert_eq!(map, expected_map);
+
+        let lines = to_lines("AAA = (BBB, CCC)\r");
+        assert_eq!(
+            parse_network_entry(&lines[0]).unwrap(),
+            ("AAA".to_owned(), ("BBB".to_owned(), "CCC".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_instruction_summary() {
+        let input = to_lines(EXAMPLE);
+        let map: Map = (input.as_slice()).try_into().unwrap();
+
+        assert_eq!(map.instruction_summary(), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_neighborhood() {
+        let input = to_lines(EXAMPLE_2);
+        let map: Map = (input.as_slice()).try_into().unwrap();
+
+        let sizes = (0..4)
+            .map(|depth| map.neighborhood("11A", depth).network.len())
+            .collect_vec();
+
+        assert_eq!(sizes, vec![1, 3, 4, 4]);
+
+        let neighborhood = map.neighborhood("22A", 1);
+        assert_eq!(neighborhood.moves, map.moves);
+        assert_eq!(
+            neighborhood.network.keys().sorted().collect_vec(),
+            vec!["22A", "22B", "XXX"]
+        );
+        assert_eq!(neighborhood.network["22B"], map.network["22B"]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let input = to_lines(EXAMPLE);
+        let map: Map = (input.as_slice()).try_into().unwrap();
+
+        let dot = map.to_dot();
+
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.contains("\"AAA\" -> \"BBB\" [style=solid];"));
+        assert!(dot.contains("\"AAA\" -> \"BBB\" [style=dashed];"));
+        assert!(dot.contains("\"BBB\" -> \"AAA\" [style=solid];"));
+        assert!(dot.contains("\"BBB\" -> \"ZZZ\" [style=dashed];"));
+        assert!(dot.contains("\"ZZZ\" -> \"ZZZ\" [style=solid];"));
+        assert_eq!(dot.lines().count(), 8);
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
@@ -188,10 +666,293 @@ LR
 XXX = (XXX, XXX)
 ";
 
+    #[test]
+    fn test_steps_to_end_cached() {
+        for example in [EXAMPLE, EXAMPLE_2] {
+            let input = to_lines(example);
+            let map: Map = (input.as_slice()).try_into().unwrap();
+            let transitions = Transitions::new(&map).unwrap();
+
+            for start in map.network.keys().filter(|key| key.ends_with('A')) {
+                assert_eq!(
+                    steps_to_end_cached(&transitions, start),
+                    steps_to_end(&map, start).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_steps_to_end_cached_no_end() {
+        let input = to_lines(
+            "\
+LR
+
+AAA = (BBB, BBB)
+BBB = (AAA, CCC)
+CCC = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+",
+        );
+        let map: Map = input.as_slice().try_into().unwrap();
+        let transitions = Transitions::new(&map).unwrap();
+
+        // ZZZ is only reached with a right move from CCC, but the walk always leaves CCC going left.
+        assert_eq!(steps_to_end_cached(&transitions, "AAA"), None);
+        assert_eq!(steps_to_end_cached(&transitions, "ZZZ"), Some(0));
+        assert_eq!(steps_to_end_cached(&transitions, "QQQ"), None);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let input = to_lines(EXAMPLE_2);
+        let map: Map = (input.as_slice()).try_into().unwrap();
+        let transitions = Transitions::new(&map).unwrap();
+
+        let cycle = find_cycle(&transitions, "11A");
+        assert_eq!(
+            cycle,
+            Cycle {
+                tail: 1,
+                period: 2,
+                z_offsets: vec![2],
+            }
+        );
+        assert!(cycle.is_clean());
+
+        let cycle = find_cycle(&transitions, "22A");
+        assert_eq!(
+            cycle,
+            Cycle {
+                tail: 1,
+                period: 6,
+                z_offsets: vec![3, 6],
+            }
+        );
+        assert!(!cycle.is_clean());
+    }
+
+    #[test]
+    fn test_steps_to_end_2_with_tails() {
+        // 11A reaches 11Z after 3, 5, 7, ... steps, and 22A after 1, 4, 7, ... steps.
+        let input = to_lines(
+            "\
+L
+
+11A = (11B, 11B)
+11B = (11C, 11C)
+11C = (11Z, 11Z)
+11Z = (11C, 11C)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+33A = (33Z, 33Z)
+33Z = (33B, 33B)
+33B = (33B, 33B)
+44A = (44Z, 44Z)
+44Z = (44Z, 44Z)
+",
+        );
+        let map: Map = (input.as_slice()).try_into().unwrap();
+        let transitions = Transitions::new(&map).unwrap();
+
+        assert_eq!(
+            find_cycle(&transitions, "11A"),
+            Cycle {
+                tail: 2,
+                period: 2,
+                z_offsets: vec![3],
+            }
+        );
+        assert_eq!(steps_to_end_2(&map, ["11A", "22A"]).unwrap(), Some(7));
+
+        // 33A is only ever on 33Z after 1 step.
+        assert_eq!(steps_to_end_2(&map, ["33A", "44A"]).unwrap(), Some(1));
+        assert_eq!(steps_to_end_2(&map, ["11A", "33A"]).unwrap(), None);
+        assert_eq!(steps_to_end_2(&map, Vec::<&str>::new()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_steps_until() {
+        let input = to_lines(EXAMPLE);
+        let map: Map = (input.as_slice()).try_into().unwrap();
+
+        assert_eq!(
+            steps_until(&map, "AAA", |pos| pos == "ZZZ").unwrap(),
+            Some(6)
+        );
+        assert_eq!(
+            steps_until(&map, "AAA", |pos| pos == "BBB").unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            steps_until(&map, "AAA", |pos| pos == "AAA").unwrap(),
+            Some(0)
+        );
+        assert_eq!(steps_until(&map, "ZZZ", |pos| pos == "AAA").unwrap(), None);
+    }
+
+    #[test]
+    fn test_steps_fast() {
+        for example in [EXAMPLE, EXAMPLE_2] {
+            let input = to_lines(example);
+            let map: Map = (input.as_slice()).try_into().unwrap();
+
+            for start in map.network.keys().filter(|key| key.ends_with('A')) {
+                assert_eq!(map.steps_fast(start, 1), steps_to_end(&map, start).unwrap());
+
+                // Walk step by step, noting every visit to a `Z` node.
+                let mut pos = start.as_str();
+                let mut visits = vec![];
+                for steps in 0..100 {
+                    if pos.ends_with('Z') {
+                        visits.push(steps);
+                    }
+                    pos = map.next_position(map.get_move_at(steps), pos).unwrap();
+                }
+
+                for (done, &steps) in (1..).zip(&visits) {
+                    assert_eq!(map.steps_fast(start, done), Some(steps));
+                }
+            }
+        }
+
+        let input = to_lines(EXAMPLE);
+        let map: Map = (input.as_slice()).try_into().unwrap();
+
+        // `ZZZ` only leads to itself, so once there every step is another visit.
+        assert_eq!(map.steps_fast("AAA", 0), Some(0));
+        assert_eq!(map.steps_fast("AAA", 5_000_000_000), Some(5_000_000_005));
+        assert_eq!(map.steps_fast("AAA", usize::MAX), None);
+        assert_eq!(map.steps_fast("XXX", 1), None);
+
+        let input = to_lines(EXAMPLE_2);
+        let map: Map = (input.as_slice()).try_into().unwrap();
+
+        // `22A` is on `22Z` after 3 and 6 steps, then every 6 steps after those.
+        assert_eq!(map.steps_fast("22A", 2_000_000_001), Some(6_000_000_003));
+        assert_eq!(map.steps_fast("22A", 2_000_000_002), Some(6_000_000_006));
+    }
+
+    #[test]
+    fn test_part1_no_path() {
+        let input = to_lines(
+            "\
+LR
+
+AAA = (BBB, BBB)
+BBB = (AAA, CCC)
+CCC = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+",
+        );
+
+        assert!(matches!(part1(&input), Err(AocError::NoPath)));
+
+        let input = to_lines(
+            "\
+LR
+
+BBB = (ZZZ, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+",
+        );
+
+        assert!(matches!(part1(&input), Err(AocError::NoPath)));
+    }
+
     #[test]
     fn test_part2() {
         let input = to_lines(EXAMPLE_2);
 
         assert_eq!(part2(&input).unwrap(), 6);
     }
+
+    #[test]
+    fn test_part2_z_in_tail() {
+        let input = to_lines(
+            "\
+L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11D, 11D)
+11D = (11E, 11E)
+11E = (11D, 11D)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+",
+        );
+        let map: Map = input.as_slice().try_into().unwrap();
+        let transitions = Transitions::new(&map).unwrap();
+
+        let cycle = find_cycle(&transitions, "11A");
+        assert_eq!(
+            cycle,
+            Cycle {
+                tail: 4,
+                period: 2,
+                z_offsets: vec![2]
+            }
+        );
+        assert!(!cycle.is_clean());
+
+        let cycle = find_cycle(&transitions, "22A");
+        assert_eq!(
+            cycle,
+            Cycle {
+                tail: 1,
+                period: 3,
+                z_offsets: vec![3]
+            }
+        );
+        assert!(cycle.is_clean());
+
+        assert!(matches!(part2(&input), Err(AocError::NoCommonEnd)));
+    }
+
+    #[test]
+    fn test_dangling_edge() {
+        let input = to_lines(
+            "\
+L
+
+11A = (11Z, 11Z)
+11Z = (11B, 11B)
+",
+        );
+        let map: Map = input.as_slice().try_into().unwrap();
+
+        assert!(matches!(
+            Transitions::new(&map),
+            Err(AocError::InvalidMap(entry)) if entry == "11Z = (11B, 11B)"
+        ));
+        assert_eq!(map.steps_fast("11A", 2), None);
+        assert!(matches!(part2(&input), Err(AocError::InvalidMap(_))));
+
+        let input = to_lines(
+            "\
+L
+
+AAA = (BBB, BBB)
+BBB = (CCC, CCC)
+",
+        );
+
+        assert!(matches!(
+            part1(&input),
+            Err(AocError::InvalidMap(entry)) if entry == "BBB = (CCC, CCC)"
+        ));
+    }
+
+    #[test]
+    fn test_part2_no_start() {
+        let input = to_lines("L\n\nBBB = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\n");
+
+        assert!(matches!(part2(&input), Err(AocError::NoCommonEnd)));
+    }
 }
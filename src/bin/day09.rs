@@ -1,107 +1,302 @@
-use std::{io, num::ParseIntError, str::FromStr};
+use std::{error, fmt, io, str::FromStr};
 
-use aoc::read_lines;
+use aoc::Solution;
 use itertools::Itertools;
+use num::{PrimInt, Signed};
 
 #[derive(Debug)]
-enum AocError {
-    IoError(io::Error),
-    ParseIntError(ParseIntError),
+pub(crate) enum AocError {
+    Common(aoc::Error),
+    InvalidToken(String),
+    /// A non-numeric `token` on the (1-based) input `line`.
+    InvalidSequence {
+        line: usize,
+        token: String,
+    },
+    /// Computing `lhs operation rhs` overflowed, whatever the integer type of the sequence.
+    Overflow {
+        operation: char,
+        lhs: i128,
+        rhs: i128,
+    },
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Common(e) => write!(f, "{e}"),
+            AocError::InvalidToken(token) => write!(f, "invalid token {token:?}"),
+            AocError::InvalidSequence { line, token } => {
+                write!(f, "invalid token {token:?} on line {line}")
+            }
+            AocError::Overflow {
+                operation,
+                lhs,
+                rhs,
+            } => write!(f, "computing {lhs} {operation} {rhs} overflowed"),
+        }
+    }
+}
+
+impl error::Error for AocError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            AocError::Common(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl From<io::Error> for AocError {
     fn from(e: io::Error) -> Self {
-        Self::IoError(e)
+        Self::Common(e.into())
     }
 }
 
-impl From<ParseIntError> for AocError {
-    fn from(e: ParseIntError) -> Self {
-        Self::ParseIntError(e)
+impl From<aoc::Error> for AocError {
+    fn from(e: aoc::Error) -> Self {
+        Self::Common(e)
     }
 }
 
-const INPUT_PATH: &str = "inputs/day09.txt";
+pub(crate) struct Day09;
 
-fn main() -> Result<(), AocError> {
-    let input = read_lines(INPUT_PATH)?;
+impl Solution for Day09 {
+    const INPUT_FILE: &'static str = "day09.txt";
 
-    println!("Part 1: {:?}", part1(&input)?);
-    println!("Part 2: {:?}", part2(&input)?);
+    type Output = i64;
+    type Error = AocError;
+
+    fn part1(input: &[String]) -> Result<i64, AocError> {
+        part1(input)
+    }
 
-    Ok(())
+    fn part2(input: &[String]) -> Result<i64, AocError> {
+        part2(input)
+    }
 }
 
-struct Sequence(Vec<i64>);
+// Unused when the day is compiled as a module of `all` or of a benchmark.
+#[allow(dead_code)]
+fn main() -> Result<(), AocError> {
+    aoc::run::<Day09>()
+}
 
-impl FromStr for Sequence {
+/// A sequence of integers of type `T`, which only needs to be wider than `i64` for extreme inputs.
+struct Sequence<T = i64>(Vec<T>);
+
+impl<T: FromStr> FromStr for Sequence<T> {
     type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers = s.split(' ').map(|n| n.parse()).try_collect()?;
+        let numbers = s
+            .split(' ')
+            .map(|n| n.parse().map_err(|_| AocError::InvalidToken(n.to_owned())))
+            .try_collect()?;
 
         Ok(Self(numbers))
     }
 }
 
-impl Sequence {
-    fn create_diff_sequence(self) -> Self {
-        let numbers = self
+fn parse_sequences(input: &[String]) -> Result<Vec<Sequence>, AocError> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            line.parse().map_err(|e| match e {
+                AocError::InvalidToken(token) => AocError::InvalidSequence {
+                    line: index + 1,
+                    token,
+                },
+                e => e,
+            })
+        })
+        .collect()
+}
+
+impl<T: PrimInt + Signed + Into<i128>> Sequence<T> {
+    /// The sequence of differences, and whether those differences are all zero.
+    #[cfg(test)]
+    fn create_diff_sequence(self) -> Result<(Self, bool), AocError> {
+        let mut is_zero = true;
+
+        let numbers: Vec<_> = self
             .0
             .into_iter()
             .tuple_windows()
-            .map(|(a, b)| b - a)
-            .collect();
+            .map(|(a, b)| {
+                let diff = checked_sub(b, a)?;
+                is_zero &= diff.is_zero();
+
+                Ok::<_, AocError>(diff)
+            })
+            .try_collect()?;
+
+        Ok((Self(numbers), is_zero))
+    }
+
+    /// Every level of differences, from the sequence itself down to the first all-zero level.
+    #[cfg(test)]
+    fn difference_triangle(&self) -> Result<Vec<Vec<T>>, AocError> {
+        let mut triangle = vec![self.0.clone()];
+        let mut current = Self(self.0.clone());
+
+        loop {
+            let (diff, diff_is_zero) = current.create_diff_sequence()?;
+            triangle.push(diff.0.clone());
+
+            if diff_is_zero {
+                return Ok(triangle);
+            }
+
+            current = diff;
+        }
+    }
+
+    /// The last and first terms of every level of the difference triangle. Extrapolating sums the
+    /// last terms, and extrapolating backwards alternately adds and subtracts the first ones.
+    #[cfg(test)]
+    fn boundary_terms(&self) -> Result<(Vec<T>, Vec<T>), AocError> {
+        let triangle = self.difference_triangle()?;
+
+        let lasts = triangle.iter().filter_map(|level| level.last()).copied();
+        let firsts = triangle.iter().filter_map(|level| level.first()).copied();
+
+        Ok((lasts.collect(), firsts.collect()))
+    }
+
+    /// The difference triangle with one CSV row per level.
+    #[cfg(test)]
+    fn triangle_csv(&self) -> Result<String, AocError>
+    where
+        T: fmt::Display,
+    {
+        let rows = self
+            .difference_triangle()?
+            .into_iter()
+            .map(|level| format!("{}\n", level.iter().join(",")));
 
-        Self(numbers)
+        Ok(rows.collect())
     }
 
-    fn is_zero(&self) -> bool {
-        self.0.iter().all(|n| *n == 0)
+    fn extrapolate(self) -> Result<T, AocError> {
+        let mut numbers = self.0;
+        let mut extrapolated = T::zero();
+
+        while let Some(&last) = numbers.last() {
+            extrapolated = checked_add(extrapolated, last)?;
+
+            if differentiate_in_place(&mut numbers)? {
+                break;
+            }
+        }
+
+        Ok(extrapolated)
     }
 
-    fn extrapolate(self) -> i64 {
-        if self.is_zero() {
-            return 0;
+    /// Like `extrapolate`, but fitting a polynomial of degree at most `degree`: the sequence is
+    /// differenced at most `degree` times, and that last level is taken to be constant even if it
+    /// is not zero (e.g. because of noise).
+    #[cfg(test)]
+    fn extrapolate_degree(&self, degree: usize) -> Result<T, AocError> {
+        let mut numbers = self.0.clone();
+        let mut extrapolated = T::zero();
+
+        for _ in 0..degree {
+            let Some(&last) = numbers.last() else {
+                break;
+            };
+            extrapolated = checked_add(extrapolated, last)?;
+
+            if differentiate_in_place(&mut numbers)? {
+                break;
+            }
         }
 
-        let last = *self.0.last().unwrap();
-        let diff = self.create_diff_sequence();
+        let last = numbers.last().copied().unwrap_or_else(T::zero);
 
-        last + diff.extrapolate()
+        checked_add(extrapolated, last)
     }
 
-    fn extrapolate_backwards(self) -> i64 {
-        if self.is_zero() {
-            return 0;
+    fn extrapolate_backwards(self) -> Result<T, AocError> {
+        let mut numbers = self.0;
+        let mut extrapolated = T::zero();
+        let mut sign = T::one();
+
+        while let Some(&first) = numbers.first() {
+            extrapolated = checked_add(extrapolated, checked_mul(sign, first)?)?;
+            sign = -sign;
+
+            if differentiate_in_place(&mut numbers)? {
+                break;
+            }
         }
 
-        let first = *self.0.first().unwrap();
-        let diff = self.create_diff_sequence();
+        Ok(extrapolated)
+    }
+}
+
+/// Replace `numbers` with their differences (one fewer), returning whether those are all zero.
+fn differentiate_in_place<T: PrimInt + Into<i128>>(numbers: &mut Vec<T>) -> Result<bool, AocError> {
+    let mut is_zero = true;
 
-        first - diff.extrapolate_backwards()
+    for i in 1..numbers.len() {
+        let (a, b) = (numbers[i - 1], numbers[i]);
+        let diff = checked_sub(b, a)?;
+        is_zero &= diff.is_zero();
+
+        numbers[i - 1] = diff;
     }
+
+    numbers.pop();
+
+    Ok(is_zero)
+}
+
+fn overflow<T: Into<i128>>(operation: char, lhs: T, rhs: T) -> AocError {
+    AocError::Overflow {
+        operation,
+        lhs: lhs.into(),
+        rhs: rhs.into(),
+    }
+}
+
+fn checked_add<T: PrimInt + Into<i128>>(lhs: T, rhs: T) -> Result<T, AocError> {
+    lhs.checked_add(&rhs).ok_or_else(|| overflow('+', lhs, rhs))
+}
+
+fn checked_sub<T: PrimInt + Into<i128>>(lhs: T, rhs: T) -> Result<T, AocError> {
+    lhs.checked_sub(&rhs).ok_or_else(|| overflow('-', lhs, rhs))
+}
+
+fn checked_mul<T: PrimInt + Into<i128>>(lhs: T, rhs: T) -> Result<T, AocError> {
+    lhs.checked_mul(&rhs).ok_or_else(|| overflow('*', lhs, rhs))
 }
 
 fn part1(input: &[String]) -> Result<i64, AocError> {
-    let sequences: Vec<Sequence> = input.iter().map(|line| line.parse()).try_collect()?;
+    let sequences = parse_sequences(input)?;
 
-    Ok(sequences.into_iter().map(Sequence::extrapolate).sum())
+    sequences
+        .into_iter()
+        .map(Sequence::extrapolate)
+        .try_fold(0, |sum, value| checked_add(sum, value?))
 }
 
 fn part2(input: &[String]) -> Result<i64, AocError> {
-    let sequences: Vec<Sequence> = input.iter().map(|line| line.parse()).try_collect()?;
+    let sequences = parse_sequences(input)?;
 
-    Ok(sequences
+    sequences
         .into_iter()
         .map(Sequence::extrapolate_backwards)
-        .sum())
+        .try_fold(0, |sum, value| checked_add(sum, value?))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::num::ParseIntError;
+
     use aoc::to_lines;
 
     // Make sure to remove any extra indentation (otherwise it will be part of the string)
@@ -111,6 +306,232 @@ mod tests {
 10 13 16 21 30 45
 ";
 
+    #[test]
+    fn test_parse_sequences_invalid_token() {
+        let input = to_lines("0 3 6\n1 3 x6 10\n");
+
+        match parse_sequences(&input) {
+            Err(AocError::InvalidSequence { line, token }) => {
+                assert_eq!(line, 2);
+                assert_eq!(token, "x6");
+            }
+            _ => panic!("expected an invalid sequence error"),
+        }
+    }
+
+    #[test]
+    fn test_error_display() {
+        let input = to_lines("0 3 6\n1 3 x6 10\n");
+        let Err(error) = parse_sequences(&input) else {
+            panic!("expected an invalid sequence error");
+        };
+
+        assert_eq!(error.to_string(), r#"invalid token "x6" on line 2"#);
+        assert!(error::Error::source(&error).is_none());
+
+        let error: AocError = aoc::parse_int::<i64>("x6").unwrap_err().into();
+        assert_eq!(
+            error.to_string(),
+            r#"invalid integer "x6": invalid digit found in string"#
+        );
+        let source = error::Error::source(&error).unwrap();
+        assert!(source.is::<aoc::Error>());
+        assert!(source.to_string().contains("x6"));
+        assert!(source.source().is_some_and(|e| e.is::<ParseIntError>()));
+    }
+
+    #[test]
+    fn test_extrapolate_constant_sequence() {
+        let sequence: Sequence = "7 7 7 7".parse().unwrap();
+        let (diff, diff_is_zero) = sequence.create_diff_sequence().unwrap();
+
+        assert_eq!(diff.0, vec![0, 0, 0]);
+        assert!(diff_is_zero);
+
+        let sequence: Sequence = "7 7 7 7".parse().unwrap();
+        assert_eq!(sequence.extrapolate().unwrap(), 7);
+
+        let sequence: Sequence = "7 7 7 7".parse().unwrap();
+        assert_eq!(sequence.extrapolate_backwards().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_triangle_csv() {
+        let sequence: Sequence = "0 3 6 9 12 15".parse().unwrap();
+
+        assert_eq!(
+            sequence.triangle_csv().unwrap(),
+            "0,3,6,9,12,15\n3,3,3,3,3\n0,0,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_boundary_terms() {
+        let sequence: Sequence = "10 13 16 21 30 45".parse().unwrap();
+        let (lasts, firsts) = sequence.boundary_terms().unwrap();
+
+        assert_eq!(lasts, vec![45, 15, 6, 2, 0]);
+        assert_eq!(firsts, vec![10, 3, 0, 2, 0]);
+
+        assert_eq!(lasts.iter().sum::<i64>(), sequence.extrapolate().unwrap());
+    }
+
+    #[test]
+    fn test_extrapolate_degree() {
+        for (line, degree) in [
+            ("0 3 6 9 12 15", 1),
+            ("1 3 6 10 15 21", 2),
+            ("10 13 16 21 30 45", 3),
+        ] {
+            let sequence: Sequence = line.parse().unwrap();
+            let expected = sequence.extrapolate_degree(degree).unwrap();
+
+            assert_eq!(sequence.extrapolate_degree(degree + 2).unwrap(), expected);
+            assert_eq!(sequence.extrapolate().unwrap(), expected);
+        }
+
+        // Too low a degree keeps the last differences, which aren't constant.
+        let sequence: Sequence = "1 3 6 10 15 21".parse().unwrap();
+        assert_eq!(sequence.extrapolate_degree(0).unwrap(), 21);
+        assert_eq!(sequence.extrapolate_degree(1).unwrap(), 27);
+
+        // The second differences are 2, 1, 1, 1: only the last of them is taken as constant.
+        let noisy: Sequence = "1 3 7 12 18 25".parse().unwrap();
+        assert_eq!(noisy.extrapolate_degree(2).unwrap(), 25 + 7 + 1);
+
+        let empty: Sequence = Sequence(vec![]);
+        assert_eq!(empty.extrapolate_degree(2).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_extrapolate_overflow() {
+        let sequence = Sequence(vec![i64::MIN, i64::MAX]);
+
+        assert!(matches!(
+            sequence.extrapolate(),
+            Err(AocError::Overflow { operation: '-', lhs, rhs })
+                if lhs == i64::MAX.into() && rhs == i64::MIN.into()
+        ));
+    }
+
+    #[test]
+    fn test_extrapolate_sum_overflow() {
+        // The differences fit, but adding the last one to the last number doesn't.
+        let input = to_lines("9223372036854775806 9223372036854775807");
+        let error = part1(&input).unwrap_err();
+
+        assert!(matches!(
+            error,
+            AocError::Overflow { operation: '+', lhs, rhs: 1 } if lhs == i64::MAX.into()
+        ));
+        assert_eq!(
+            error.to_string(),
+            "computing 9223372036854775807 + 1 overflowed"
+        );
+        assert_eq!(part2(&input).unwrap(), i64::MAX - 2);
+
+        // Going backwards, the first difference is negated.
+        let input = to_lines(&format!("{} {}", i64::MIN, i64::MIN + 1));
+        assert!(matches!(
+            part2(&input),
+            Err(AocError::Overflow { operation: '+', .. })
+        ));
+
+        // Every sequence fits, but their sum doesn't.
+        let input = to_lines("4611686018427387904 4611686018427387904\n4611686018427387904\n");
+        assert!(matches!(
+            part1(&input),
+            Err(AocError::Overflow { operation: '+', .. })
+        ));
+        assert!(matches!(
+            part2(&input),
+            Err(AocError::Overflow { operation: '+', .. })
+        ));
+    }
+
+    #[test]
+    fn test_extrapolate_i128() {
+        let input = format!("{} {}", i64::MIN, i64::MAX);
+        let difference = i128::from(i64::MAX) - i128::from(i64::MIN);
+
+        assert!(input.parse::<Sequence>().unwrap().extrapolate().is_err());
+
+        let sequence: Sequence<i128> = input.parse().unwrap();
+        assert_eq!(
+            sequence.extrapolate().unwrap(),
+            i128::from(i64::MAX) + difference
+        );
+
+        let sequence: Sequence<i128> = input.parse().unwrap();
+        assert_eq!(
+            sequence.extrapolate_backwards().unwrap(),
+            i128::from(i64::MIN) - difference
+        );
+
+        let sequence: Sequence<i16> = "10 13 16 21 30 45".parse().unwrap();
+        assert_eq!(sequence.extrapolate().unwrap(), 68);
+    }
+
+    /// The recursive definition of extrapolating forwards (`sign = 1`) or backwards (`sign = -1`).
+    fn extrapolate_recursive(numbers: &[i64], sign: i64) -> i64 {
+        let diff = numbers
+            .iter()
+            .tuple_windows()
+            .map(|(a, b)| b - a)
+            .collect_vec();
+        let boundary = if sign > 0 {
+            numbers.last()
+        } else {
+            numbers.first()
+        };
+
+        match boundary {
+            None => 0,
+            Some(&boundary) if diff.iter().all(|&d| d == 0) => boundary,
+            Some(&boundary) => boundary + sign * extrapolate_recursive(&diff, sign),
+        }
+    }
+
+    #[test]
+    fn test_extrapolate_iterative() {
+        let input = to_lines(EXAMPLE);
+
+        let forwards: i64 = parse_sequences(&input)
+            .unwrap()
+            .into_iter()
+            .map(|sequence| sequence.extrapolate().unwrap())
+            .sum();
+        let backwards: i64 = parse_sequences(&input)
+            .unwrap()
+            .into_iter()
+            .map(|sequence| sequence.extrapolate_backwards().unwrap())
+            .sum();
+
+        assert_eq!(forwards, 114);
+        assert_eq!(backwards, 2);
+
+        // Powers of two are their own differences, so every level down to a single term is needed.
+        let sequences = [
+            (0..50).map(|n| 1 << n).collect_vec(),
+            (0..200)
+                .map(|n: i64| n.pow(3) - 7 * n.pow(2) + 5)
+                .collect_vec(),
+            vec![],
+            vec![42],
+        ];
+
+        for numbers in sequences {
+            assert_eq!(
+                Sequence(numbers.clone()).extrapolate().unwrap(),
+                extrapolate_recursive(&numbers, 1)
+            );
+            assert_eq!(
+                Sequence(numbers.clone()).extrapolate_backwards().unwrap(),
+                extrapolate_recursive(&numbers, -1)
+            );
+        }
+    }
+
     #[test]
     fn test_part1() {
         let input = to_lines(EXAMPLE);
@@ -1,17 +1,592 @@
+#[cfg(feature = "test-hooks")]
+use std::cell::RefCell;
+#[cfg(feature = "timings")]
+use std::time::{Duration, Instant};
 use std::{
+    env, error,
+    fmt::{self, Display},
     fs::File,
-    io::{self, BufRead, BufReader},
-    path::Path,
+    io::{self, BufRead, BufReader, IsTerminal},
+    num::ParseIntError,
+    path::{Path, PathBuf},
+    str::FromStr,
 };
 
-pub fn read_lines(path: &str) -> io::Result<Vec<String>> {
-    let path = Path::new(path);
-    let file = File::open(path)?;
+/// Errors every day can run into. Days with errors of their own wrap this in their `AocError`.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// A `token` that is not a valid integer.
+    ParseInt {
+        token: String,
+        source: ParseIntError,
+    },
+    /// Some input that could not be parsed.
+    Parse(String),
+    /// Values that should have come in pairs, of which there are this odd number.
+    OddLength(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::ParseInt { token, source } => write!(f, "invalid integer {token:?}: {source}"),
+            Self::Parse(input) => write!(f, "could not parse {input:?}"),
+            Self::OddLength(length) => {
+                write!(f, "expected pairs of values, got {length} values")
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::ParseInt { source, .. } => Some(source),
+            Self::Parse(_) | Self::OddLength(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Parse `token` as an integer, keeping the token in the error if it is not one.
+pub fn parse_int<T: FromStr<Err = ParseIntError>>(token: &str) -> Result<T, Error> {
+    token.parse().map_err(|source| Error::ParseInt {
+        token: token.to_owned(),
+        source,
+    })
+}
+
+/// A day's puzzle, solved from the lines of its input.
+///
+/// ```
+/// use aoc::Solution;
+///
+/// struct Day00;
+///
+/// impl Solution for Day00 {
+///     const INPUT_FILE: &'static str = "day00.txt";
+///
+///     type Output = usize;
+///     type Error = std::io::Error;
+///
+///     fn part1(input: &[String]) -> Result<usize, Self::Error> {
+///         Ok(input.len())
+///     }
+///
+///     fn part2(input: &[String]) -> Result<usize, Self::Error> {
+///         Ok(input.iter().map(String::len).sum())
+///     }
+/// }
+///
+/// let input = aoc::to_lines("ab\ncde\n");
+///
+/// assert_eq!(Day00::part1(&input).unwrap(), 2);
+/// assert_eq!(Day00::part2(&input).unwrap(), 5);
+/// ```
+///
+/// The day's `main` is then `aoc::run::<Day00>()`.
+pub trait Solution {
+    /// The name of the input file, in [`input_dir`].
+    const INPUT_FILE: &'static str;
+
+    type Output: Display;
+    type Error: From<io::Error>;
+
+    fn part1(input: &[String]) -> Result<Self::Output, Self::Error>;
+    fn part2(input: &[String]) -> Result<Self::Output, Self::Error>;
+
+    fn input_path() -> PathBuf {
+        input_dir().join(Self::INPUT_FILE)
+    }
+
+    /// Solve both parts, timing them. Days that parse their input once for both parts should
+    /// override this to report the parsing separately; by default it is counted as solving.
+    #[cfg(feature = "timings")]
+    fn solve(input: &[String]) -> Result<Solved, Self::Error> {
+        let start = Instant::now();
+        let part1 = Self::part1(input)?;
+        let part2 = Self::part2(input)?;
+
+        Ok(Solved {
+            part1: part1.to_string(),
+            part2: part2.to_string(),
+            parse_time: Duration::ZERO,
+            solve_time: start.elapsed(),
+        })
+    }
+}
+
+/// The answers to both parts of a day, along with how long it took to get them.
+#[cfg(feature = "timings")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solved {
+    pub part1: String,
+    pub part2: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+#[cfg(feature = "timings")]
+impl fmt::Display for Solved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Part 1: {}", self.part1)?;
+        writeln!(f, "Part 2: {}", self.part2)?;
+        write!(
+            f,
+            "Parsed in {:?}, solved in {:?}",
+            self.parse_time, self.solve_time
+        )
+    }
+}
+
+/// Read the input of `S` and print the answers to both parts.
+#[cfg(not(feature = "timings"))]
+pub fn run<S: Solution>() -> Result<(), S::Error> {
+    let input = read_lines(cli_input_path::<S>())?;
+
+    println!("Part 1: {}", S::part1(&input)?);
+    println!("Part 2: {}", S::part2(&input)?);
+
+    Ok(())
+}
+
+/// Read the input of `S` and print the answers to both parts, along with how long they took.
+#[cfg(feature = "timings")]
+pub fn run<S: Solution>() -> Result<(), S::Error> {
+    let input = read_lines(cli_input_path::<S>())?;
+
+    println!("{}", S::solve(&input)?);
+
+    Ok(())
+}
+
+/// The input file of `S` to use when running it: the one given on the command line if any, see
+/// [`input_path_from_args`].
+pub fn cli_input_path<S: Solution>() -> PathBuf {
+    input_path_from_args(env::args().skip(1), S::input_path())
+}
+
+/// The first of `args` (without the program name) that is not a flag such as `--stats`, or
+/// `default` if there is none.
+pub fn input_path_from_args(args: impl IntoIterator<Item = String>, default: PathBuf) -> PathBuf {
+    args.into_iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or(default, PathBuf::from)
+}
+
+/// The directory with the puzzle inputs: `inputs/` in the crate root, so that days can be run
+/// from anywhere. Falls back to `inputs/` in the current directory when the crate root it was
+/// built from is gone.
+pub fn input_dir() -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    if manifest_dir.is_dir() {
+        manifest_dir.join("inputs")
+    } else {
+        PathBuf::from("inputs")
+    }
+}
+
+#[cfg(feature = "test-hooks")]
+thread_local! {
+    static TEST_INPUT: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Make `read_lines` return `lines` on the current thread, whatever the path, so that a day's
+/// `main` can be run in tests without an input file.
+#[cfg(feature = "test-hooks")]
+pub fn set_test_input(lines: Vec<String>) {
+    TEST_INPUT.set(Some(lines));
+}
+
+/// Go back to `read_lines` reading files on the current thread.
+#[cfg(feature = "test-hooks")]
+pub fn clear_test_input() {
+    TEST_INPUT.set(None);
+}
+
+/// Read all the lines of the file at `path`. Errors mention the path they happened for.
+pub fn read_lines(path: impl AsRef<Path>) -> io::Result<Vec<String>> {
+    #[cfg(feature = "test-hooks")]
+    if let Some(lines) = TEST_INPUT.with_borrow(Clone::clone) {
+        return Ok(lines);
+    }
+
+    read_lines_iter(path)?.collect()
+}
+
+/// Read the lines of the file at `path`, dropping those for which `skip` holds (e.g. comments).
+pub fn read_lines_filtered(
+    path: impl AsRef<Path>,
+    skip: impl Fn(&str) -> bool,
+) -> io::Result<Vec<String>> {
+    read_lines_iter(path)?
+        .filter(|line| line.as_ref().map_or(true, |line| !skip(line)))
+        .collect()
+}
+
+/// Lazily read the lines of the file at `path`, without buffering the whole file. Errors mention
+/// the path they happened for, including those reading a line. With the `gzip` feature, files
+/// ending in `.gz` are decompressed as they are read, including every member of concatenated
+/// gzip files.
+pub fn read_lines_iter(
+    path: impl AsRef<Path>,
+) -> io::Result<impl Iterator<Item = io::Result<String>>> {
+    let file = File::open(&path);
+
+    let path = path.as_ref().to_owned();
+    #[cfg(feature = "gzip")]
+    let gzipped = path.extension().is_some_and(|extension| extension == "gz");
+    let with_path =
+        move |e: io::Error| io::Error::new(e.kind(), format!("{}: {e}", path.display()));
+
+    let file = file.map_err(&with_path)?;
+    #[cfg(not(feature = "gzip"))]
     let reader = BufReader::new(file);
+    #[cfg(feature = "gzip")]
+    let reader: Box<dyn BufRead> = if gzipped {
+        Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(file)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    Ok(reader
+        .lines()
+        .map(move |line| line.map(strip_cr).map_err(&with_path)))
+}
 
-    reader.lines().collect()
+/// Drop the `\r` of a CRLF line ending. `lines` already does this, except for a final line that
+/// has no `\n` after it.
+fn strip_cr(mut line: String) -> String {
+    if line.ends_with('\r') {
+        line.pop();
+    }
+
+    line
 }
 
+/// Split `data` into lines, like `read_lines` would a file. A final newline ends the last line
+/// rather than starting an empty one, so `"a\nb\n"` gives `["a", "b"]` while `"a\nb\n\n"` gives
+/// `["a", "b", ""]`. CRLF line endings are accepted too.
 pub fn to_lines(data: &str) -> Vec<String> {
-    data.lines().map(|s| s.to_owned()).collect()
+    data.lines().map(|s| strip_cr(s.to_owned())).collect()
+}
+
+/// Split `line` once on `sep` into a label and a body, trimming whitespace around both.
+pub fn split_label<'a>(line: &'a str, sep: &str) -> Option<(&'a str, &'a str)> {
+    let (label, body) = line.split_once(sep)?;
+
+    Some((label.trim(), body.trim()))
+}
+
+/// Group `slice` into consecutive pairs, failing with [`Error::OddLength`] if it has an odd
+/// length.
+pub fn pairs<T: Copy>(slice: &[T]) -> Result<Vec<(T, T)>, Error> {
+    let chunks = slice.chunks_exact(2);
+
+    if !chunks.remainder().is_empty() {
+        return Err(Error::OddLength(slice.len()));
+    }
+
+    Ok(chunks.map(|pair| (pair[0], pair[1])).collect())
+}
+
+/// Percentage between two progress reports.
+const PROGRESS_STEP: usize = 5;
+
+/// An iterator reporting how much of it has been consumed, see [`with_progress`].
+pub struct Progress<I> {
+    inner: I,
+    total: usize,
+    consumed: usize,
+    reported: usize,
+    enabled: bool,
+}
+
+/// Wrap `iter`, which is expected to yield `total` items, so that the percentage consumed is
+/// printed to stderr as it goes. Does nothing when stderr is not a terminal.
+pub fn with_progress<I: IntoIterator>(total: usize, iter: I) -> Progress<I::IntoIter> {
+    Progress {
+        inner: iter.into_iter(),
+        total,
+        consumed: 0,
+        reported: 0,
+        enabled: io::stderr().is_terminal(),
+    }
+}
+
+impl<I: Iterator> Iterator for Progress<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next();
+
+        if !self.enabled || self.total == 0 {
+            return item;
+        }
+
+        if item.is_some() {
+            self.consumed += 1;
+
+            let percent = self.consumed * 100 / self.total;
+            if percent >= self.reported + PROGRESS_STEP {
+                self.reported = percent - percent % PROGRESS_STEP;
+                eprint!("\r{percent}%");
+            }
+        } else if self.reported > 0 {
+            eprintln!();
+            self.reported = 0;
+        }
+
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_lines_missing_file() {
+        let error = read_lines("inputs/missing.txt").unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().starts_with("inputs/missing.txt: "));
+    }
+
+    #[test]
+    fn test_input_dir() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let input_dir = input_dir();
+
+        assert!(input_dir.is_absolute());
+        assert!(input_dir.starts_with(manifest_dir));
+        assert_eq!(input_dir, manifest_dir.join("inputs"));
+
+        let error = read_lines(input_dir.join("missing.txt")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_input_path_from_args() {
+        let default = PathBuf::from("inputs/day01.txt");
+        let path = |args: &[&str]| {
+            let args = args.iter().map(|&arg| arg.to_owned());
+
+            input_path_from_args(args, default.clone())
+        };
+
+        assert_eq!(path(&[]), default);
+        assert_eq!(path(&["--stats"]), default);
+        assert_eq!(path(&["example.txt"]), PathBuf::from("example.txt"));
+        assert_eq!(
+            path(&["--verbose", "example.txt", "other.txt"]),
+            PathBuf::from("example.txt")
+        );
+    }
+
+    /// A file in the temporary directory, removed when dropped.
+    struct TempFile(PathBuf);
+
+    impl AsRef<Path> for TempFile {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl fmt::Display for TempFile {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0.display())
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Write `contents` to a fresh file in the temporary directory, which is removed once the
+    /// returned `TempFile` goes out of scope.
+    fn temp_file(name: &str, contents: &[u8]) -> TempFile {
+        let path = std::env::temp_dir().join(format!("aoc-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+
+        TempFile(path)
+    }
+
+    #[test]
+    fn test_temp_file_removed() {
+        let file = temp_file("removed.txt", b"a\n");
+        let path = file.0.clone();
+        assert!(path.exists());
+
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_read_lines_iter() {
+        let path = temp_file("read_lines_iter.txt", b"first\n\nthird\n");
+
+        let lines: Vec<String> = read_lines_iter(&path)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(lines, read_lines(&path).unwrap());
+        assert_eq!(lines, vec!["first", "", "third"]);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_read_lines_gzip() {
+        use std::io::Write;
+
+        use flate2::{write::GzEncoder, Compression};
+
+        let contents = b"first\n\nthird\r\n";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents).unwrap();
+
+        let gzipped = temp_file("read_lines_gzip.txt.gz", &encoder.finish().unwrap());
+        let plain = temp_file("read_lines_gzip.txt", contents);
+
+        assert_eq!(read_lines(&gzipped).unwrap(), read_lines(&plain).unwrap());
+        assert_eq!(read_lines(&gzipped).unwrap(), vec!["first", "", "third"]);
+
+        // Concatenated gzip files (as with `cat a.gz b.gz`) are read to the end.
+        let mut concatenated = vec![];
+        for part in [&b"first\n"[..], b"second\n"] {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(part).unwrap();
+            concatenated.extend(encoder.finish().unwrap());
+        }
+        let concatenated = temp_file("read_lines_gzip_members.txt.gz", &concatenated);
+        assert_eq!(read_lines(&concatenated).unwrap(), vec!["first", "second"]);
+
+        // Only the extension matters: a plain file named `.gz` is not valid gzip.
+        let not_gzipped = temp_file("read_lines_not_gzip.txt.gz", contents);
+        assert!(read_lines(&not_gzipped).is_err());
+    }
+
+    #[test]
+    fn test_read_lines_iter_error_mid_stream() {
+        let path = temp_file("read_lines_iter_error.txt", b"first\n\xff\nthird\n");
+
+        let mut lines = read_lines_iter(&path).unwrap();
+
+        assert_eq!(lines.next().unwrap().unwrap(), "first");
+
+        let error = lines.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with(&format!("{path}: ")));
+
+        assert!(read_lines(&path).is_err());
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(parse_int::<usize>("42").unwrap(), 42);
+
+        let error = parse_int::<usize>("4x2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"invalid integer "4x2": invalid digit found in string"#
+        );
+        assert!(error::Error::source(&error).is_some_and(|e| e.is::<ParseIntError>()));
+
+        let error = Error::Parse("Game one: 3 blue".to_owned());
+        assert_eq!(error.to_string(), r#"could not parse "Game one: 3 blue""#);
+
+        let error: Error = read_lines("inputs/missing.txt").unwrap_err().into();
+        assert!(error
+            .to_string()
+            .starts_with("I/O error: inputs/missing.txt: "));
+    }
+
+    #[test]
+    fn test_to_lines_trailing_newlines() {
+        assert_eq!(to_lines("a\nb"), vec!["a", "b"]);
+        assert_eq!(to_lines("a\nb\n"), vec!["a", "b"]);
+        assert_eq!(to_lines("a\nb\n\n"), vec!["a", "b", ""]);
+        assert_eq!(to_lines("a\n\nb\n"), vec!["a", "", "b"]);
+        assert_eq!(to_lines(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_read_lines_filtered() {
+        let path = temp_file(
+            "read_lines_filtered.txt",
+            b"# Comment\nTime: 7 15\n  # Indented comment\n\nDistance: 9 # 40\n",
+        );
+
+        let lines = read_lines_filtered(&path, |line| line.trim_start().starts_with('#')).unwrap();
+
+        assert_eq!(lines, vec!["Time: 7 15", "", "Distance: 9 # 40"]);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        assert_eq!(to_lines("a\r\nb\r\n\r\n"), vec!["a", "b", ""]);
+        assert_eq!(to_lines("a\r\nb\r"), vec!["a", "b"]);
+
+        let path = temp_file("crlf.txt", b"a\r\nb\r\n\r\nc\r");
+        assert_eq!(read_lines(&path).unwrap(), vec!["a", "b", "", "c"]);
+    }
+
+    #[test]
+    fn test_to_lines_matches_read_lines() {
+        let path = temp_file("to_lines.txt", b"a\nb\n\n");
+
+        assert_eq!(to_lines("a\nb\n\n"), read_lines(&path).unwrap());
+    }
+
+    #[test]
+    fn test_split_label() {
+        assert_eq!(
+            split_label("Game 1: 3 blue, 4 red", ": "),
+            Some(("Game 1", "3 blue, 4 red"))
+        );
+        assert_eq!(
+            split_label("Time:      7  15   30 ", ":"),
+            Some(("Time", "7  15   30"))
+        );
+        assert_eq!(split_label("a: b: c", ": "), Some(("a", "b: c")));
+        assert_eq!(split_label("no separator", ": "), None);
+    }
+
+    #[test]
+    fn test_pairs() {
+        assert_eq!(pairs(&[79, 14, 55, 13]).unwrap(), vec![(79, 14), (55, 13)]);
+        assert_eq!(pairs::<usize>(&[]).unwrap(), vec![]);
+
+        let error = pairs(&[79, 14, 55]).unwrap_err();
+        assert!(matches!(error, Error::OddLength(3)));
+        assert_eq!(error.to_string(), "expected pairs of values, got 3 values");
+    }
+
+    #[test]
+    fn test_with_progress() {
+        let items: Vec<_> = with_progress(1000, 0..1000).collect();
+
+        assert_eq!(items, (0..1000).collect::<Vec<_>>());
+
+        let mut progress = with_progress(100, 0..1000);
+        progress.enabled = true;
+
+        assert_eq!(progress.count(), 1000);
+    }
 }